@@ -0,0 +1,71 @@
+//! Throughput of parsing raw Eden notifications into `EdenItem`s
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use eden_rs::json_rpc::notification::EdenItem;
+
+const BATCH: usize = 1_000;
+
+const ACCESS_LIST: &str = r#"[{"address":"0x19c10fff96b80208f454034c046ccc4445cd20ba","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000001","0x0000000000000000000000000000000000000000000000000000000000000002"]}]"#;
+
+// a notification for a tx of `ty` carrying `data_len` bytes of calldata
+fn notification(ty: u8, data_len: usize, access_list: bool) -> String {
+    let data = "ab".repeat(data_len);
+    let fees = match ty {
+        0 => r#""gasPrice":"0xc570bd200""#.to_string(),
+        _ => r#""maxPriorityFeePerGas":"0x2faf080","maxFeePerGas":"0xc570bd200""#.to_string(),
+    };
+    let access_list = match (ty, access_list) {
+        (0, _) => String::new(),
+        (_, true) => format!(r#","accessList":{ACCESS_LIST}"#),
+        (_, false) => r#","accessList":[]"#.to_string(),
+    };
+
+    format!(
+        r#"{{"jsonrpc":"2.0","method":"subscription","params":{{"subscription":4815270595554998,"result":{{"type":"0x{ty}","hash":"0xd2bd5a7fa523f13e7f955c0753cd2f1de0635b6c165c2494aae44d8bbdd9a9c6","from":"0x19450678803d6a7bb6897ca1e793a071a100cba7","nonce":"0x2","gasLimit":"0x7a120","to":"0x19c10fff96b80208f454034c046ccc4445cd20ba","data":"0x{data}","v":"0x1","r":"0xe6e52e08bf9735e38c1808285269afef6b82d500cd5a90966479b5f8fa70e623","s":"0x21490c9a52a60b2c3a5a6045d687dbe8a5e710274aa3071b813a1bf24271eb45","value":"0x83019dfc17b0000","chainId":"0x1"{access_list},{fees}}}}}}}"#
+    )
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_notification");
+    group.throughput(Throughput::Elements(BATCH as u64));
+
+    let kinds = [
+        ("legacy", 0, false),
+        ("eip1559", 2, false),
+        ("eip1559_access_list", 2, true),
+        ("eip4844", 3, true),
+    ];
+    for (name, ty, access_list) in kinds {
+        for data_len in [0, 256, 4096] {
+            let payload = notification(ty, data_len, access_list);
+            assert!(matches!(
+                serde_json::from_str::<EdenItem>(&payload),
+                Ok(EdenItem::Notification(_))
+            ));
+            let batch = vec![payload; BATCH];
+
+            group.bench_with_input(BenchmarkId::new(name, data_len), &batch, |b, batch| {
+                b.iter(|| {
+                    for payload in batch {
+                        black_box(serde_json::from_str::<EdenItem>(payload).unwrap());
+                    }
+                })
+            });
+
+            // text frames arrive as an owned `String` already, this only skips the UTF-8 check
+            let id = BenchmarkId::new(format!("{name}_from_slice"), data_len);
+            group.bench_with_input(id, &batch, |b, batch| {
+                b.iter(|| {
+                    for payload in batch {
+                        black_box(serde_json::from_slice::<EdenItem>(payload.as_bytes()).unwrap());
+                    }
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    future,
+    time::Duration,
+};
+
+use ethers_core::types::H256;
+use futures_util::{Stream, StreamExt};
+use tokio::time::Instant;
+
+use crate::types::EdenPendingTx;
+
+// hashes seen within the window, oldest first
+struct Seen {
+    hashes: HashSet<H256>,
+    order: VecDeque<(Instant, H256)>,
+    window: Duration,
+    capacity: usize,
+}
+
+impl Seen {
+    // remembers `hash`, `false` if it was already seen within the window
+    fn insert(&mut self, hash: H256) -> bool {
+        let now = Instant::now();
+        while let Some(&(seen_at, oldest)) = self.order.front() {
+            if now.duration_since(seen_at) < self.window {
+                break;
+            }
+            self.order.pop_front();
+            self.hashes.remove(&oldest);
+        }
+
+        if self.hashes.contains(&hash) {
+            return false;
+        }
+
+        if self.order.len() >= self.capacity {
+            if let Some((_, oldest)) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        self.order.push_back((now, hash));
+        self.hashes.insert(hash);
+
+        true
+    }
+}
+
+/// Drops txs whose hash was already yielded within the last `window`, e.g.
+/// on a feed merged from several endpoints.
+///
+/// A hash is forgotten `window` after it was first yielded, so a tx seen
+/// again later, e.g. after being dropped and resubmitted, is delivered again.
+/// Duplicates don't extend the window. At most `capacity` hashes are kept,
+/// evicting the oldest first, which bounds memory under bursts at the cost of
+/// letting through duplicates of evicted hashes. A few minutes, roughly how
+/// long a tx stays relevant in the mempool, is a sensible window.
+pub fn dedup<S>(stream: S, window: Duration, capacity: usize) -> impl Stream<Item = EdenPendingTx>
+where
+    S: Stream<Item = EdenPendingTx>,
+{
+    let mut seen = Seen {
+        hashes: HashSet::new(),
+        order: VecDeque::new(),
+        window,
+        capacity: capacity.max(1),
+    };
+
+    stream.filter(move |tx| future::ready(seen.insert(tx.hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn tx(hash: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.hash = H256::from_low_u64_be(hash);
+        tx
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn redelivers_after_the_window() {
+        let (sender, rx) = mpsc::unbounded_channel();
+        let deduped = dedup(
+            UnboundedReceiverStream::new(rx),
+            Duration::from_secs(300),
+            100,
+        );
+        tokio::pin!(deduped);
+
+        sender.send(tx(1)).unwrap();
+        assert_eq!(deduped.next().await.unwrap().hash, tx(1).hash);
+
+        // a recent duplicate is suppressed
+        tokio::time::advance(Duration::from_secs(299)).await;
+        sender.send(tx(1)).unwrap();
+        sender.send(tx(2)).unwrap();
+        assert_eq!(deduped.next().await.unwrap().hash, tx(2).hash);
+
+        // the first sighting expired, the second's didn't
+        tokio::time::advance(Duration::from_secs(1)).await;
+        sender.send(tx(1)).unwrap();
+        sender.send(tx(2)).unwrap();
+        sender.send(tx(3)).unwrap();
+        assert_eq!(deduped.next().await.unwrap().hash, tx(1).hash);
+        assert_eq!(deduped.next().await.unwrap().hash, tx(3).hash);
+    }
+
+    #[tokio::test]
+    async fn evicts_the_oldest_hash_at_capacity() {
+        let txs = stream::iter([tx(1), tx(2), tx(3), tx(1), tx(3)]);
+        let hashes: Vec<_> = dedup(txs, Duration::from_secs(300), 2)
+            .map(|tx| tx.hash.to_low_u64_be())
+            .collect()
+            .await;
+
+        assert_eq!(hashes, [1, 2, 3, 1]);
+    }
+}
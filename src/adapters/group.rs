@@ -0,0 +1,123 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ethers_core::types::Address;
+use futures_util::{stream, Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::types::EdenPendingTx;
+
+/// Txs of a single sender, see [`by_sender`]
+pub type SenderStream = UnboundedReceiverStream<EdenPendingTx>;
+
+// per-sender channels keyed by address, `recency` orders them by last activity
+struct Senders {
+    channels: HashMap<Address, (mpsc::UnboundedSender<EdenPendingTx>, u64)>,
+    recency: BTreeMap<u64, Address>,
+    tick: u64,
+    max_senders: usize,
+}
+
+impl Senders {
+    // routes `tx` to its sender's channel, returning the receiver if a new channel was opened
+    fn route(&mut self, tx: EdenPendingTx) -> Option<SenderStream> {
+        self.tick += 1;
+        let from = tx.from;
+
+        if let Some((channel, last_active)) = self.channels.get_mut(&from) {
+            self.recency.remove(last_active);
+            *last_active = self.tick;
+            self.recency.insert(self.tick, from);
+
+            // reopen the channel if the consumer dropped its stream
+            match channel.send(tx) {
+                Ok(()) => return None,
+                Err(mpsc::error::SendError(tx)) => return Some(self.open(from, tx)),
+            }
+        }
+
+        if self.channels.len() >= self.max_senders {
+            if let Some((_, evicted)) = self.recency.pop_first() {
+                // dropping the sender ends the evicted stream
+                self.channels.remove(&evicted);
+            }
+        }
+        self.recency.insert(self.tick, from);
+
+        Some(self.open(from, tx))
+    }
+
+    fn open(&mut self, from: Address, tx: EdenPendingTx) -> SenderStream {
+        let (channel, rx) = mpsc::unbounded_channel();
+        let _ = channel.send(tx);
+        self.channels.insert(from, (channel, self.tick));
+        UnboundedReceiverStream::new(rx)
+    }
+}
+
+/// Demultiplexes `stream` into one stream per `from` address.
+///
+/// The returned control stream yields `(sender, txs)` the first time a sender
+/// is seen, later txs of that sender go to its stream. At most `max_senders`
+/// are tracked; opening one more ends the stream of the least recently active
+/// sender, and a sender seen again after eviction is announced anew, as is
+/// one whose stream was dropped. Per-sender streams only receive txs while
+/// the control stream is polled, and all of them end once `stream` ends.
+pub fn by_sender<S>(stream: S, max_senders: usize) -> impl Stream<Item = (Address, SenderStream)>
+where
+    S: Stream<Item = EdenPendingTx> + Unpin,
+{
+    let senders = Senders {
+        channels: HashMap::new(),
+        recency: BTreeMap::new(),
+        tick: 0,
+        max_senders: max_senders.max(1),
+    };
+
+    stream::unfold((stream, senders), |(mut stream, mut senders)| async move {
+        while let Some(tx) = stream.next().await {
+            let from = tx.from;
+            if let Some(txs) = senders.route(tx) {
+                return Some(((from, txs), (stream, senders)));
+            }
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn from(sender: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.from = Address::from_low_u64_be(sender);
+        tx.nonce = sender.into();
+        tx
+    }
+
+    #[tokio::test]
+    async fn groups_and_evicts_least_recent() {
+        let txs = [from(1), from(2), from(1), from(3), from(2)];
+        let mut control = Box::pin(by_sender(stream::iter(txs), 2));
+
+        let (first, mut ones) = control.next().await.unwrap();
+        let (second, mut twos) = control.next().await.unwrap();
+        // 3 evicts 2, since 1 was active more recently
+        let (third, _) = control.next().await.unwrap();
+        let (again, _) = control.next().await.unwrap();
+        assert!(control.next().await.is_none());
+
+        assert_eq!(
+            [first, second, third, again].map(|a| a.to_low_u64_be()),
+            [1, 2, 3, 2]
+        );
+        assert_eq!(ones.next().await.unwrap().from, first);
+        assert_eq!(ones.next().await.unwrap().from, first);
+        assert_eq!(twos.next().await.unwrap().from, second);
+        assert!(twos.next().await.is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use ethers_core::types::{TransactionReceipt, H256};
+use eyre::Result;
+use futures_util::{Stream, StreamExt};
+use tokio::time::Instant;
+
+use crate::types::EdenPendingTx;
+
+/// Looks up tx receipts on a node.
+///
+/// Implement this for your own provider by issuing
+/// `eth_getTransactionReceipt`:
+///
+/// ```ignore
+/// struct Node(Provider<Ws>);
+///
+/// impl ReceiptProvider for Node {
+///     fn receipt(&self, hash: H256) -> impl Future<Output = Result<Option<TransactionReceipt>>> + Send {
+///         async move { Ok(self.0.get_transaction_receipt(hash).await?) }
+///     }
+/// }
+/// ```
+pub trait ReceiptProvider {
+    /// Receipt of `hash`, `None` while the tx is not included
+    fn receipt(
+        &self,
+        hash: H256,
+    ) -> impl Future<Output = Result<Option<TransactionReceipt>>> + Send;
+}
+
+/// Polling schedule used by [`track_inclusion`]
+#[derive(Debug, Clone)]
+pub struct InclusionPolling {
+    /// Give up on a tx this long after it was first polled
+    pub timeout: Duration,
+    /// Delay before the second poll
+    pub initial_interval: Duration,
+    /// Cap on the delay between polls
+    pub max_interval: Duration,
+}
+
+impl Default for InclusionPolling {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(120),
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(12),
+        }
+    }
+}
+
+// polls until the receipt shows up or the timeout passes
+async fn poll_receipt<P: ReceiptProvider>(
+    provider: &P,
+    hash: H256,
+    polling: &InclusionPolling,
+) -> Option<TransactionReceipt> {
+    let deadline = Instant::now() + polling.timeout;
+    let mut interval = polling.initial_interval;
+
+    loop {
+        match provider.receipt(hash).await {
+            Ok(Some(receipt)) => return Some(receipt),
+            Ok(None) => {}
+            Err(e) => tracing::debug!(?hash, error = ?e, "Failed to fetch receipt"),
+        }
+
+        if Instant::now() + interval > deadline {
+            return None;
+        }
+        tokio::time::sleep(interval).await;
+        interval = (interval * 2).min(polling.max_interval);
+    }
+}
+
+/// Joins every tx of `stream` with its receipt once it lands on chain.
+///
+/// Each tx is polled immediately, then after `initial_interval`, doubling the
+/// delay up to `max_interval`, until a receipt is found or `timeout` passes.
+/// Txs that were dropped or replaced, or whose lookups keep failing, yield
+/// `None`. At most `max_concurrency` txs are tracked at once and items are
+/// yielded as soon as they resolve, so they don't keep the feed order.
+pub fn track_inclusion<S, P>(
+    stream: S,
+    provider: P,
+    polling: InclusionPolling,
+    max_concurrency: usize,
+) -> impl Stream<Item = (EdenPendingTx, Option<TransactionReceipt>)>
+where
+    S: Stream<Item = EdenPendingTx>,
+    P: ReceiptProvider,
+{
+    let provider = Arc::new(provider);
+    let polling = Arc::new(polling);
+
+    stream
+        .map(move |tx| {
+            let provider = provider.clone();
+            let polling = polling.clone();
+            async move {
+                let receipt = poll_receipt(provider.as_ref(), tx.hash, &polling).await;
+                (tx, receipt)
+            }
+        })
+        .buffer_unordered(max_concurrency.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    /// Includes the tx on the third lookup
+    #[derive(Default)]
+    struct Node {
+        lookups: AtomicUsize,
+    }
+
+    impl ReceiptProvider for &Node {
+        fn receipt(
+            &self,
+            hash: H256,
+        ) -> impl Future<Output = Result<Option<TransactionReceipt>>> + Send {
+            let lookup = self.lookups.fetch_add(1, Ordering::SeqCst);
+            async move {
+                Ok((lookup >= 2).then(|| TransactionReceipt {
+                    transaction_hash: hash,
+                    ..Default::default()
+                }))
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn included_after_polling() {
+        let node = Node::default();
+        let polling = InclusionPolling::default();
+
+        let mut joined = track_inclusion(stream::iter([sample_tx()]), &node, polling, 4);
+
+        let (tx, receipt) = joined.next().await.unwrap();
+        assert_eq!(receipt.unwrap().transaction_hash, tx.hash);
+        assert_eq!(node.lookups.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn times_out_to_none() {
+        let node = Node::default();
+        let polling = InclusionPolling {
+            timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+
+        let mut joined = track_inclusion(stream::iter([sample_tx()]), &node, polling, 4);
+
+        let (_, receipt) = joined.next().await.unwrap();
+        assert!(receipt.is_none());
+    }
+}
@@ -0,0 +1,65 @@
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::types::EdenPendingTx;
+
+/// Which stream of [`merge_tagged`] produced an item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The first stream, `a`
+    First,
+    /// The second stream, `b`
+    Second,
+}
+
+/// Interleaves `a` and `b`, tagging every tx with the stream it came from.
+///
+/// Both streams are polled in turn so neither starves the other. Txs seen by
+/// both are yielded twice, once per source. The merged stream ends once both
+/// inputs have ended.
+pub fn merge_tagged<A, B>(a: A, b: B) -> impl Stream<Item = (Source, EdenPendingTx)>
+where
+    A: Stream<Item = EdenPendingTx>,
+    B: Stream<Item = EdenPendingTx>,
+{
+    stream::select(
+        a.map(|tx| (Source::First, tx)),
+        b.map(|tx| (Source::Second, tx)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::U256;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn nonce(nonce: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.nonce = nonce.into();
+        tx
+    }
+
+    #[tokio::test]
+    async fn tags_items_with_their_source() {
+        let a = stream::iter([nonce(0), nonce(1)]);
+        let b = stream::iter([nonce(10), nonce(11), nonce(12)]);
+
+        let mut merged: Vec<_> = merge_tagged(a, b)
+            .map(|(source, tx)| (source, tx.nonce))
+            .collect()
+            .await;
+        merged.sort_by_key(|(_, nonce)| *nonce);
+
+        assert_eq!(
+            merged,
+            vec![
+                (Source::First, U256::from(0)),
+                (Source::First, U256::from(1)),
+                (Source::Second, U256::from(10)),
+                (Source::Second, U256::from(11)),
+                (Source::Second, U256::from(12)),
+            ]
+        );
+    }
+}
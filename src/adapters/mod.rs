@@ -0,0 +1,29 @@
+//! Stream combinators over subscription feeds
+
+mod dedup;
+mod group;
+#[cfg(feature = "inclusion")]
+mod inclusion;
+mod merge;
+mod nonce;
+mod reorder;
+#[cfg(feature = "simulate")]
+mod simulate;
+mod snapshot;
+#[cfg(feature = "dex")]
+mod swap;
+mod tee;
+
+pub use dedup::dedup;
+pub use group::{by_sender, SenderStream};
+#[cfg(feature = "inclusion")]
+pub use inclusion::{track_inclusion, InclusionPolling, ReceiptProvider};
+pub use merge::{merge_tagged, Source};
+pub use nonce::nonce_order;
+pub use reorder::reorder;
+#[cfg(feature = "simulate")]
+pub use simulate::{simulate, Simulator};
+pub use snapshot::{collect_for, diff, SnapshotDiff};
+#[cfg(feature = "dex")]
+pub use swap::{is_swap_like, swap_like};
+pub use tee::{tee, tee_bounded};
@@ -0,0 +1,236 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    time::Duration,
+};
+
+use ethers_core::types::{Address, U256};
+use futures_util::{
+    stream::{self, Fuse, FusedStream},
+    Stream, StreamExt,
+};
+use tokio::time::{sleep_until, Instant};
+
+use crate::types::EdenPendingTx;
+
+// txs of one sender waiting for a nonce gap to fill
+struct Queue {
+    next: U256,
+    held: BTreeMap<U256, EdenPendingTx>,
+    // gap flush time while txs are held, otherwise when the sender is forgotten
+    deadline: Instant,
+}
+
+impl Queue {
+    // moves held txs continuing the sequence into `ready`
+    fn release(&mut self, ready: &mut VecDeque<EdenPendingTx>) {
+        while let Some(tx) = self.held.remove(&self.next) {
+            self.next = tx.nonce + 1;
+            ready.push_back(tx);
+        }
+    }
+}
+
+struct State<S> {
+    stream: Fuse<S>,
+    queues: HashMap<Address, Queue>,
+    deadlines: BTreeSet<(Instant, Address)>,
+    ready: VecDeque<EdenPendingTx>,
+    flush_after: Duration,
+    max_held: usize,
+}
+
+impl<S> State<S> {
+    fn push(&mut self, tx: EdenPendingTx) {
+        let now = Instant::now();
+        let (from, nonce) = (tx.from, tx.nonce);
+
+        let Some(queue) = self.queues.get_mut(&from) else {
+            // the first tx seen from a sender sets the expected nonce
+            let deadline = now + self.flush_after;
+            self.ready.push_back(tx);
+            self.queues.insert(
+                from,
+                Queue {
+                    next: nonce + 1,
+                    held: BTreeMap::new(),
+                    deadline,
+                },
+            );
+            self.deadlines.insert((deadline, from));
+            return;
+        };
+
+        let had_gap = !queue.held.is_empty();
+        if nonce < queue.next {
+            // replacement or late tx, nothing to wait for
+            self.ready.push_back(tx);
+        } else if nonce == queue.next {
+            queue.next = nonce + 1;
+            self.ready.push_back(tx);
+            queue.release(&mut self.ready);
+        } else {
+            queue.held.insert(nonce, tx);
+            if queue.held.len() > self.max_held {
+                // give up on the gap rather than growing the buffer
+                if let Some((_, lowest)) = queue.held.pop_first() {
+                    queue.next = lowest.nonce + 1;
+                    self.ready.push_back(lowest);
+                    queue.release(&mut self.ready);
+                }
+            }
+        }
+
+        // a gap keeps its original deadline, otherwise the sender stays known a while longer
+        if !(had_gap && !queue.held.is_empty()) {
+            self.deadlines.remove(&(queue.deadline, from));
+            queue.deadline = now + self.flush_after;
+            self.deadlines.insert((queue.deadline, from));
+        }
+    }
+
+    fn expire(&mut self, from: Address) {
+        let Some(mut queue) = self.queues.remove(&from) else {
+            return;
+        };
+        if queue.held.is_empty() {
+            return;
+        }
+
+        // flush the stuck sequence and keep following the sender from its end
+        for (nonce, tx) in std::mem::take(&mut queue.held) {
+            queue.next = nonce + 1;
+            self.ready.push_back(tx);
+        }
+        queue.deadline = Instant::now() + self.flush_after;
+        self.deadlines.insert((queue.deadline, from));
+        self.queues.insert(from, queue);
+    }
+
+    fn flush_all(&mut self) {
+        for (_, queue) in self.queues.drain() {
+            self.ready.extend(queue.held.into_values());
+        }
+        self.deadlines.clear();
+    }
+}
+
+/// Releases each sender's txs in ascending nonce order.
+///
+/// The first tx seen from a sender sets its expected nonce. Txs arriving
+/// after a nonce gap are held until the gap fills, for at most `flush_after`,
+/// after which the held txs are released in order despite the gap. Txs with a
+/// nonce below the expected one, e.g. replacements, pass through immediately.
+/// At most `max_held` txs are held per sender, one more releases the lowest
+/// held tx and abandons its gap. A sender is forgotten `flush_after` after
+/// its last tx. Ordering across senders is not preserved, and held txs are
+/// flushed once `stream` ends.
+pub fn nonce_order<S>(
+    stream: S,
+    flush_after: Duration,
+    max_held: usize,
+) -> impl Stream<Item = EdenPendingTx>
+where
+    S: Stream<Item = EdenPendingTx> + Unpin,
+{
+    let state = State {
+        stream: stream.fuse(),
+        queues: HashMap::new(),
+        deadlines: BTreeSet::new(),
+        ready: VecDeque::new(),
+        flush_after,
+        max_held: max_held.max(1),
+    };
+
+    stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(tx) = state.ready.pop_front() {
+                return Some((tx, state));
+            }
+            if state.stream.is_terminated() {
+                if state.queues.is_empty() {
+                    return None;
+                }
+                state.flush_all();
+                continue;
+            }
+
+            let next_deadline = state.deadlines.first().map(|(deadline, _)| *deadline);
+
+            tokio::select! {
+                item = state.stream.next() => {
+                    if let Some(tx) = item {
+                        state.push(tx);
+                    }
+                }
+                _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                    if let Some((_, from)) = state.deadlines.pop_first() {
+                        state.expire(from);
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn tx(sender: u64, nonce: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.from = Address::from_low_u64_be(sender);
+        tx.nonce = nonce.into();
+        tx
+    }
+
+    fn keys(txs: &[EdenPendingTx]) -> Vec<(u64, u64)> {
+        txs.iter()
+            .map(|tx| (tx.from.to_low_u64_be(), tx.nonce.as_u64()))
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fills_gaps_per_sender() {
+        let txs = [tx(1, 1), tx(1, 3), tx(2, 5), tx(1, 2), tx(2, 4)];
+
+        let ordered: Vec<_> = nonce_order(stream::iter(txs), Duration::from_secs(1), 8)
+            .collect()
+            .await;
+
+        assert_eq!(keys(&ordered), [(1, 1), (2, 5), (1, 2), (1, 3), (2, 4)]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flushes_stuck_gap_after_timeout() {
+        let (sender, rx) = mpsc::unbounded_channel();
+        let mut ordered = Box::pin(nonce_order(
+            UnboundedReceiverStream::new(rx),
+            Duration::from_secs(1),
+            8,
+        ));
+
+        sender.send(tx(1, 1)).unwrap();
+        sender.send(tx(1, 3)).unwrap();
+        assert_eq!(ordered.next().await.unwrap().nonce, 1.into());
+
+        let start = Instant::now();
+        assert_eq!(ordered.next().await.unwrap().nonce, 3.into());
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bounds_held_txs() {
+        let txs = [tx(1, 1), tx(1, 3), tx(1, 4), tx(1, 2)];
+
+        let ordered: Vec<_> = nonce_order(stream::iter(txs), Duration::from_secs(1), 1)
+            .collect()
+            .await;
+
+        assert_eq!(keys(&ordered), [(1, 1), (1, 3), (1, 4), (1, 2)]);
+    }
+}
@@ -0,0 +1,143 @@
+use std::{cmp::Ordering, collections::BinaryHeap, time::Duration};
+
+use futures_util::{
+    stream::{self, FusedStream},
+    Stream, StreamExt,
+};
+use tokio::time::{sleep_until, Instant};
+
+use crate::types::Sequenced;
+
+// item waiting in the reorder buffer, the heap yields the earliest `received_at` first
+struct Held<T> {
+    item: Sequenced<T>,
+    release_at: Instant,
+}
+
+impl<T> Held<T> {
+    fn key(&self) -> (std::time::SystemTime, u64) {
+        (self.item.received_at, self.item.seq)
+    }
+}
+
+impl<T> PartialEq for Held<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<T> Eq for Held<T> {}
+
+impl<T> PartialOrd for Held<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Held<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed to turn the max-heap into a min-heap
+        other.key().cmp(&self.key())
+    }
+}
+
+/// Buffers items and releases them sorted by their `received_at` timestamp.
+///
+/// Every item is held for at least `delay` after it reaches the buffer, so
+/// this adds `delay` of latency to the feed, more for items that arrive
+/// before an older straggler. Items arriving within `delay` of each other are
+/// emitted in timestamp order, which smooths out jitter between merged
+/// multi-region feeds. Remaining items are flushed in order once `stream`
+/// ends.
+pub fn reorder<S, T>(stream: S, delay: Duration) -> impl Stream<Item = Sequenced<T>>
+where
+    S: Stream<Item = Sequenced<T>> + Unpin,
+{
+    stream::unfold(
+        (stream.fuse(), BinaryHeap::new()),
+        move |(mut stream, mut held)| async move {
+            loop {
+                if stream.is_terminated() {
+                    return held.pop().map(|Held { item, .. }| (item, (stream, held)));
+                }
+
+                let next_release = held.peek().map(|h: &Held<T>| h.release_at);
+
+                tokio::select! {
+                    item = stream.next() => {
+                        if let Some(item) = item {
+                            held.push(Held {
+                                item,
+                                release_at: Instant::now() + delay,
+                            });
+                        }
+                    }
+                    _ = sleep_until(next_release.unwrap_or_else(Instant::now)), if next_release.is_some() => {
+                        if let Some(Held { item, .. }) = held.pop() {
+                            return Some((item, (stream, held)));
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use tokio::sync::mpsc;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
+    use super::*;
+
+    fn sequenced(seq: u64, received_at: SystemTime) -> Sequenced<u64> {
+        Sequenced {
+            seq,
+            received_at,
+            snapshot: false,
+            label: None,
+            raw: None,
+            item: seq,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn releases_sorted_after_delay() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut out = Box::pin(reorder(
+            UnboundedReceiverStream::new(rx),
+            Duration::from_millis(100),
+        ));
+
+        let now = SystemTime::now();
+        tx.send(sequenced(1, now + Duration::from_millis(5)))
+            .unwrap();
+        tx.send(sequenced(0, now)).unwrap();
+
+        // nothing is released before the delay elapses
+        let early = tokio::time::timeout(Duration::from_millis(50), out.next()).await;
+        assert!(early.is_err());
+
+        assert_eq!(out.next().await.unwrap().seq, 0);
+        assert_eq!(out.next().await.unwrap().seq, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn flushes_in_order_on_end() {
+        let now = SystemTime::now();
+        let items = vec![
+            sequenced(2, now + Duration::from_millis(2)),
+            sequenced(0, now),
+            sequenced(1, now + Duration::from_millis(1)),
+        ];
+
+        let seqs: Vec<_> = reorder(stream::iter(items), Duration::from_secs(60))
+            .map(|item| item.seq)
+            .collect()
+            .await;
+
+        assert_eq!(seqs, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::{future::Future, sync::Arc};
+
+use futures_util::{Stream, StreamExt};
+
+use crate::types::EdenPendingTx;
+
+/// Simulates pending txs against a node.
+///
+/// Implement this for your own provider, e.g. by issuing `eth_call` or
+/// `trace_call` with [`EdenPendingTx::into_ethers_tx`]:
+///
+/// ```ignore
+/// struct Node(Provider<Ws>);
+///
+/// impl Simulator for Node {
+///     type Output = Result<Bytes, ProviderError>;
+///
+///     fn simulate(&self, tx: &EdenPendingTx) -> impl Future<Output = Self::Output> + Send {
+///         let tx = TypedTransaction::from(&tx.clone().into_ethers_tx());
+///         async move { self.0.call(&tx, None).await }
+///     }
+/// }
+/// ```
+pub trait Simulator {
+    /// Result of a single simulation
+    type Output;
+
+    /// Simulate `tx` against the node
+    fn simulate(&self, tx: &EdenPendingTx) -> impl Future<Output = Self::Output> + Send;
+}
+
+/// Simulates every tx of `stream`, yielding it together with the result.
+///
+/// At most `max_concurrency` simulations run at once and results keep the
+/// order of the feed. Once the limit is reached the feed is no longer polled,
+/// so incoming txs queue in the subscription channel until a slot frees up.
+pub fn simulate<S, Sim>(
+    stream: S,
+    simulator: Sim,
+    max_concurrency: usize,
+) -> impl Stream<Item = (EdenPendingTx, Sim::Output)>
+where
+    S: Stream<Item = EdenPendingTx>,
+    Sim: Simulator,
+{
+    let simulator = Arc::new(simulator);
+
+    stream
+        .map(move |tx| {
+            let simulator = simulator.clone();
+            async move {
+                let output = simulator.simulate(&tx).await;
+                (tx, output)
+            }
+        })
+        .buffered(max_concurrency.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use ethers_core::types::U256;
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    #[derive(Default)]
+    struct SlowNode {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl Simulator for &SlowNode {
+        type Output = U256;
+
+        fn simulate(&self, tx: &EdenPendingTx) -> impl Future<Output = Self::Output> + Send {
+            let nonce = tx.nonce;
+            let node = *self;
+            async move {
+                let now = node.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                node.max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                node.in_flight.fetch_sub(1, Ordering::SeqCst);
+                nonce
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn bounded_and_ordered() {
+        let node = SlowNode::default();
+        let txs = (0..10u64).map(|nonce| {
+            let mut tx = sample_tx();
+            tx.nonce = nonce.into();
+            tx
+        });
+
+        let results: Vec<_> = simulate(stream::iter(txs), &node, 3).collect().await;
+
+        assert_eq!(results.len(), 10);
+        for (nonce, (tx, output)) in results.into_iter().enumerate() {
+            assert_eq!(tx.nonce, U256::from(nonce));
+            assert_eq!(output, tx.nonce);
+        }
+        assert!(node.max_in_flight.load(Ordering::SeqCst) <= 3);
+    }
+}
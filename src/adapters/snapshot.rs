@@ -0,0 +1,127 @@
+use std::{collections::HashMap, time::Duration};
+
+use ethers_core::types::{Address, H256, U256};
+use futures_util::{Stream, StreamExt};
+
+use crate::types::EdenPendingTx;
+
+/// Consumes `stream` for `duration` and indexes the txs seen by hash.
+///
+/// A tx seen more than once, e.g. delivered again after a reconnect, keeps
+/// its last delivery. Returns early if `stream` ends before the timeout.
+pub async fn collect_for<S>(stream: S, duration: Duration) -> HashMap<H256, EdenPendingTx>
+where
+    S: Stream<Item = EdenPendingTx> + Unpin,
+{
+    let stream = stream.take_until(tokio::time::sleep(duration));
+    tokio::pin!(stream);
+
+    let mut snapshot = HashMap::new();
+    while let Some(tx) = stream.next().await {
+        snapshot.insert(tx.hash, tx);
+    }
+
+    snapshot
+}
+
+/// Changes between two snapshots, see [`diff`]
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDiff {
+    /// Txs only in the new snapshot, excluding replacements
+    pub added: Vec<EdenPendingTx>,
+    /// Txs only in the old snapshot, excluding replaced ones
+    pub removed: Vec<EdenPendingTx>,
+    /// `(old, new)` pairs with the same `from` and `nonce` but a different hash
+    pub replaced: Vec<(EdenPendingTx, EdenPendingTx)>,
+}
+
+/// Compares two snapshots, e.g. from [`collect_for`], by tx hash.
+///
+/// A tx missing from `new` whose sender and nonce reappear under another
+/// hash, e.g. after a fee bump, counts as replaced rather than removed and
+/// added. Txs within each list are in no particular order.
+pub fn diff(
+    old: &HashMap<H256, EdenPendingTx>,
+    new: &HashMap<H256, EdenPendingTx>,
+) -> SnapshotDiff {
+    let mut added: HashMap<(Address, U256), &EdenPendingTx> = new
+        .values()
+        .filter(|tx| !old.contains_key(&tx.hash))
+        .map(|tx| ((tx.from, tx.nonce), tx))
+        .collect();
+
+    let mut diff = SnapshotDiff::default();
+    for tx in old.values().filter(|tx| !new.contains_key(&tx.hash)) {
+        match added.remove(&(tx.from, tx.nonce)) {
+            Some(replacement) => diff.replaced.push((tx.clone(), replacement.clone())),
+            None => diff.removed.push(tx.clone()),
+        }
+    }
+    diff.added = added.into_values().cloned().collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::U256;
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    #[tokio::test(start_paused = true)]
+    async fn dedups_until_timeout() {
+        let first = sample_tx();
+        let mut resent = sample_tx();
+        resent.gas_limit = U256::from(21_000);
+        let mut other = sample_tx();
+        other.hash = H256::repeat_byte(1);
+
+        // stays open after the txs, only the timeout ends collection
+        let txs = stream::iter([first.clone(), resent, other]).chain(stream::pending());
+        let snapshot = collect_for(txs, Duration::from_secs(1)).await;
+
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[&first.hash].gas_limit, U256::from(21_000));
+    }
+
+    fn snapshot(txs: &[EdenPendingTx]) -> HashMap<H256, EdenPendingTx> {
+        txs.iter().map(|tx| (tx.hash, tx.clone())).collect()
+    }
+
+    fn tx(hash: u64, from: u64, nonce: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.hash = H256::from_low_u64_be(hash);
+        tx.from = Address::from_low_u64_be(from);
+        tx.nonce = nonce.into();
+        tx
+    }
+
+    #[test]
+    fn diffs_snapshots() {
+        let kept = tx(1, 1, 0);
+        let dropped = tx(2, 2, 0);
+        let bumped = tx(3, 3, 0);
+        let bump = tx(4, 3, 0);
+        let next_nonce = tx(5, 1, 1);
+
+        let old = snapshot(&[kept.clone(), dropped.clone(), bumped.clone()]);
+        let new = snapshot(&[kept, bump.clone(), next_nonce.clone()]);
+
+        let hashes = |txs: &[EdenPendingTx]| txs.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+        let diff = diff(&old, &new);
+        assert_eq!(hashes(&diff.added), [next_nonce.hash]);
+        assert_eq!(hashes(&diff.removed), [dropped.hash]);
+        assert_eq!(diff.replaced.len(), 1);
+        assert_eq!(diff.replaced[0].0.hash, bumped.hash);
+        assert_eq!(diff.replaced[0].1.hash, bump.hash);
+    }
+
+    #[test]
+    fn identical_snapshots_have_no_diff() {
+        let txs = snapshot(&[tx(1, 1, 0), tx(2, 1, 1)]);
+        let diff = diff(&txs, &txs);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.replaced.is_empty());
+    }
+}
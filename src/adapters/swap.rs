@@ -0,0 +1,98 @@
+use ethers_core::types::U256;
+use futures_util::{Stream, StreamExt};
+
+use crate::types::EdenPendingTx;
+
+/// Selectors of common Uniswap V2/V3 router and Universal Router swaps
+const SWAP_SELECTORS: [[u8; 4]; 12] = [
+    // V2: swapExactETHForTokens, swapETHForExactTokens
+    [0x7f, 0xf3, 0x6a, 0xb5],
+    [0xfb, 0x3b, 0xdb, 0x41],
+    // V2: swapExactTokensForTokens, swapTokensForExactTokens
+    [0x38, 0xed, 0x17, 0x39],
+    [0x88, 0x03, 0xdb, 0xee],
+    // V2: swapExactTokensForETH, swapTokensForExactETH
+    [0x18, 0xcb, 0xaf, 0xe5],
+    [0x4a, 0x25, 0xd9, 0x4a],
+    // V3: exactInputSingle, exactInput, exactOutputSingle, exactOutput
+    [0x41, 0x4b, 0xf3, 0x89],
+    [0xc0, 0x4b, 0x8d, 0x59],
+    [0xdb, 0x3e, 0x21, 0x98],
+    [0xf2, 0x8c, 0x04, 0x98],
+    // Universal Router: execute(bytes,bytes[],uint256), execute(bytes,bytes[])
+    [0x35, 0x93, 0x56, 0x4c],
+    [0x24, 0x85, 0x6b, 0xc3],
+];
+
+/// Conservative check whether `tx` looks like a DEX swap.
+///
+/// A tx is swap-like when its calldata starts with one of a fixed set of
+/// well known router swap selectors and it moves a non-zero amount: either
+/// non-zero `value` or a non-zero first argument (the input amount or the
+/// offset of the params struct). Aggregators, proxies and unknown routers are
+/// not detected, so `false` does not mean the tx cannot be sandwiched.
+pub fn is_swap_like(tx: &EdenPendingTx) -> bool {
+    let Some((selector, args)) = tx.data.split_first_chunk::<4>() else {
+        return false;
+    };
+    if !SWAP_SELECTORS.contains(selector) {
+        return false;
+    }
+
+    let first_arg = args
+        .get(..32)
+        .map(U256::from_big_endian)
+        .unwrap_or_default();
+    !tx.value.is_zero() || !first_arg.is_zero()
+}
+
+/// Tags every tx of `stream` with [`is_swap_like`]
+pub fn swap_like<S>(stream: S) -> impl Stream<Item = (EdenPendingTx, bool)>
+where
+    S: Stream<Item = EdenPendingTx>,
+{
+    stream.map(|tx| {
+        let swap_like = is_swap_like(&tx);
+        (tx, swap_like)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::Bytes;
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn with_calldata(selector: [u8; 4], amount: u64, value: u64) -> EdenPendingTx {
+        let mut data = selector.to_vec();
+        let mut word = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut word);
+        data.extend_from_slice(&word);
+
+        let mut tx = sample_tx();
+        tx.data = Bytes::from(data);
+        tx.value = value.into();
+        tx
+    }
+
+    #[tokio::test]
+    async fn tags_swaps() {
+        let swap_exact_tokens = [0x38, 0xed, 0x17, 0x39];
+        let transfer = [0xa9, 0x05, 0x9c, 0xbb];
+        let txs = vec![
+            with_calldata(swap_exact_tokens, 1_000, 0),
+            with_calldata(swap_exact_tokens, 0, 0),
+            with_calldata(transfer, 1_000, 0),
+            with_calldata([0x7f, 0xf3, 0x6a, 0xb5], 0, 1),
+        ];
+
+        let tags: Vec<_> = swap_like(stream::iter(txs))
+            .map(|(_, swap_like)| swap_like)
+            .collect()
+            .await;
+
+        assert_eq!(tags, [true, false, false, true]);
+    }
+}
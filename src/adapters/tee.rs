@@ -0,0 +1,120 @@
+use futures_util::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, UnboundedReceiverStream};
+
+use crate::types::EdenPendingTx;
+
+/// Forwards every tx of `stream` to both returned streams.
+///
+/// Unlike a broadcast nothing is dropped for a slow consumer, its stream
+/// buffers without bound instead, see [`tee_bounded`] to apply backpressure.
+/// The forwarding runs on a spawned task, which keeps feeding one stream
+/// after the other was dropped and stops once both are. Must be called
+/// within a tokio runtime.
+pub fn tee<S>(
+    mut stream: S,
+) -> (
+    UnboundedReceiverStream<EdenPendingTx>,
+    UnboundedReceiverStream<EdenPendingTx>,
+)
+where
+    S: Stream<Item = EdenPendingTx> + Send + Unpin + 'static,
+{
+    let (first, first_rx) = mpsc::unbounded_channel();
+    let (second, second_rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Some(tx) = stream.next().await {
+            let first_gone = first.send(tx.clone()).is_err();
+            let second_gone = second.send(tx).is_err();
+            if first_gone && second_gone {
+                break;
+            }
+        }
+    });
+
+    (
+        UnboundedReceiverStream::new(first_rx),
+        UnboundedReceiverStream::new(second_rx),
+    )
+}
+
+/// Like [`tee`], buffering at most `capacity` txs per stream.
+///
+/// The next tx is pulled from `stream` only once both streams accepted the
+/// previous one, so the slowest consumer sets the pace. Consumers have to
+/// poll both streams concurrently, reading one to the end first stalls once
+/// the other's buffer is full.
+pub fn tee_bounded<S>(
+    mut stream: S,
+    capacity: usize,
+) -> (ReceiverStream<EdenPendingTx>, ReceiverStream<EdenPendingTx>)
+where
+    S: Stream<Item = EdenPendingTx> + Send + Unpin + 'static,
+{
+    let (first, first_rx) = mpsc::channel(capacity.max(1));
+    let (second, second_rx) = mpsc::channel(capacity.max(1));
+
+    tokio::spawn(async move {
+        while let Some(tx) = stream.next().await {
+            let (first_sent, second_sent) = tokio::join!(first.send(tx.clone()), second.send(tx));
+            if first_sent.is_err() && second_sent.is_err() {
+                break;
+            }
+        }
+    });
+
+    (
+        ReceiverStream::new(first_rx),
+        ReceiverStream::new(second_rx),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::U256;
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn txs(count: u64) -> impl Stream<Item = EdenPendingTx> + Send + Unpin + 'static {
+        stream::iter((0..count).map(|nonce| {
+            let mut tx = sample_tx();
+            tx.nonce = nonce.into();
+            tx
+        }))
+    }
+
+    fn nonces(txs: Vec<EdenPendingTx>) -> Vec<U256> {
+        txs.into_iter().map(|tx| tx.nonce).collect()
+    }
+
+    #[tokio::test]
+    async fn both_streams_see_every_tx() {
+        let (first, second) = tee(txs(100));
+        let expected: Vec<U256> = (0..100u64).map(U256::from).collect();
+
+        // reading one stream to the end first is fine without a bound
+        assert_eq!(nonces(first.collect().await), expected);
+        assert_eq!(nonces(second.collect().await), expected);
+    }
+
+    #[tokio::test]
+    async fn bounded_streams_see_every_tx() {
+        let (first, second) = tee_bounded(txs(100), 1);
+        let (first, second) = tokio::join!(first.collect(), second.collect());
+
+        let expected: Vec<U256> = (0..100u64).map(U256::from).collect();
+        assert_eq!(nonces(first), expected);
+        assert_eq!(nonces(second), expected);
+    }
+
+    #[tokio::test]
+    async fn dropping_one_stream_keeps_the_other() {
+        let (first, second) = tee_bounded(txs(10), 1);
+        drop(first);
+
+        assert_eq!(second.count().await, 10);
+    }
+}
@@ -0,0 +1,282 @@
+#[cfg(all(unix, feature = "unix"))]
+use std::path::PathBuf;
+use std::{net::SocketAddr, num::NonZeroU32, sync::Arc, time::Duration};
+
+use tokio_tungstenite::{
+    tungstenite::{protocol::WebSocketConfig, Message},
+    Connector,
+};
+use url::Url;
+
+#[cfg(feature = "prometheus")]
+use crate::client::metrics::Metrics;
+#[cfg(feature = "testing")]
+use crate::client::ChaosConfig;
+use crate::client::{transport::Target, Client, ReconnectPolicy};
+
+/// Hook invoked by the read loop on every raw frame
+#[derive(Clone)]
+pub(crate) struct OnMessage(pub(crate) Arc<dyn Fn(&Message) + Send + Sync>);
+
+impl std::fmt::Debug for OnMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("OnMessage")
+    }
+}
+
+/// Connection settings shared by every subscription of a [`Client`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClientConfig {
+    /// Outbound request rate limit, `None` means unthrottled
+    pub(crate) max_requests_per_sec: Option<NonZeroU32>,
+    /// Reconnect policy, `None` ends the subscription when the connection drops
+    pub(crate) reconnect: Option<ReconnectPolicy>,
+    /// Collectors updated by every subscription
+    #[cfg(feature = "prometheus")]
+    pub(crate) metrics: Option<Metrics>,
+    /// Max time a single frame write may take, `None` uses the default
+    pub(crate) write_timeout: Option<Duration>,
+    /// Interval between keepalive pings, `None` sends none
+    pub(crate) keepalive: Option<Duration>,
+    /// Max silence on the socket before reconnecting, `None` waits forever
+    pub(crate) read_timeout: Option<Duration>,
+    /// Hook run on every received frame before parsing
+    pub(crate) on_message: Option<OnMessage>,
+    /// Endpoint label attached to subscriptions and their items
+    pub(crate) label: Option<Arc<str>>,
+    /// Max size of a frame or message, `None` uses tungstenite's default
+    pub(crate) max_message_size: Option<usize>,
+    /// Limit of reconnects after a frame exceeded `max_message_size`
+    pub(crate) fallback_max_message_size: Option<usize>,
+    /// Seed of the sampling and jitter PRNG, `None` seeds from entropy
+    pub(crate) rng_seed: Option<u64>,
+    /// Address or socket to connect to instead of resolving the url's host
+    pub(crate) target: Option<Target>,
+    /// Log every frame at trace level, `Some(true)` including its payload
+    pub(crate) trace_frames: Option<bool>,
+    /// Subprotocols offered in the handshake, in order of preference
+    pub(crate) subprotocols: Vec<String>,
+    /// Faults injected by the read loop, `None` injects none
+    #[cfg(feature = "testing")]
+    pub(crate) chaos: Option<ChaosConfig>,
+    /// Custom rustls config, `None` uses webpki roots
+    #[cfg(feature = "rustls-tls")]
+    pub(crate) tls: Option<Arc<rustls::ClientConfig>>,
+}
+
+impl ClientConfig {
+    /// TLS connector for the handshake, `None` picks the backend default
+    pub(crate) fn connector(&self) -> Option<Connector> {
+        #[cfg(feature = "rustls-tls")]
+        return self.tls.clone().map(Connector::Rustls);
+        #[cfg(not(feature = "rustls-tls"))]
+        None
+    }
+
+    /// WebSocket settings for the handshake, `None` uses tungstenite's defaults
+    pub(crate) fn ws_config(&self) -> Option<WebSocketConfig> {
+        self.max_message_size.map(message_size_limit)
+    }
+}
+
+/// WebSocket settings limiting frames and messages to `limit` bytes
+pub(crate) fn message_size_limit(limit: usize) -> WebSocketConfig {
+    WebSocketConfig {
+        max_message_size: Some(limit),
+        max_frame_size: Some(limit),
+        ..Default::default()
+    }
+}
+
+/// Builder for [`Client`]
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    url: Url,
+    config: ClientConfig,
+}
+
+impl ClientBuilder {
+    /// Initialize new builder with eden agg mempool url
+    pub fn new(wss: impl Into<Url>) -> Self {
+        Self {
+            url: wss.into(),
+            config: ClientConfig::default(),
+        }
+    }
+
+    /// Throttle outbound requests to at most `max` per second.
+    ///
+    /// Requests over the limit are queued rather than dropped. `0` disables
+    /// throttling, which is the default.
+    pub fn max_requests_per_sec(mut self, max: u32) -> Self {
+        self.config.max_requests_per_sec = NonZeroU32::new(max);
+        self
+    }
+
+    /// Reconnect and resubscribe every feed after the connection drops.
+    ///
+    /// Disabled by default, in which case the subscription ends with the
+    /// connection.
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.config.reconnect = Some(policy);
+        self
+    }
+
+    /// Give up on a frame write after `timeout`, 10 seconds by default.
+    ///
+    /// A write that doesn't complete in time, e.g. because the socket's send
+    /// buffer stays full on a slow link, fails the connection, which is then
+    /// reconnected per the [`reconnect`](Self::reconnect) policy instead of
+    /// hanging the subscription.
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.config.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Send a ping every `interval` while connected.
+    ///
+    /// Keeps idle connections open through proxies and surfaces dead links
+    /// through the write path. Disabled by default.
+    pub fn keepalive(mut self, interval: Duration) -> Self {
+        self.config.keepalive = Some(interval);
+        self
+    }
+
+    /// Reconnect when no frame at all arrives for `timeout`.
+    ///
+    /// Unlike [`SubscriptionOptions::idle_timeout`](crate::client::SubscriptionOptions::idle_timeout)
+    /// any frame counts, including pings and the pongs answering
+    /// [`keepalive`](Self::keepalive) pings, so quiet mempool periods don't
+    /// trigger it while dead sockets do. The connection is then reconnected
+    /// per the [`reconnect`](Self::reconnect) policy. Disabled by default.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.config.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Run `hook` on every frame the read loop receives, before parsing.
+    ///
+    /// Useful for lightweight instrumentation, e.g. sampling latency from a
+    /// timestamp embedded by a proxy. The hook runs inline on the read loop of
+    /// every subscription, so it should return quickly.
+    pub fn on_message(mut self, hook: impl Fn(&Message) + Send + Sync + 'static) -> Self {
+        self.config.on_message = Some(OnMessage(Arc::new(hook)));
+        self
+    }
+
+    /// Name this endpoint, e.g. by region.
+    ///
+    /// The label is exposed by [`Subscription::label`](crate::client::Subscription::label)
+    /// and stamped on every [`Sequenced`](crate::types::Sequenced) item, so
+    /// streams merged from several clients can be attributed to their source.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.config.label = Some(label.into().into());
+        self
+    }
+
+    /// Fail the connection on frames or messages larger than `bytes`.
+    ///
+    /// Defaults to tungstenite's limits of 16 MiB per frame and 64 MiB per
+    /// message. Fragmented messages are buffered until their last frame
+    /// arrives and only parsed once complete, so the message limit applies to
+    /// the reassembled payload. An oversized frame can't be skipped, since tungstenite stops
+    /// reading the connection, so it is counted in
+    /// [`StatsSnapshot::oversized_frames`](crate::client::StatsSnapshot::oversized_frames)
+    /// and the connection is reconnected per the [`reconnect`](Self::reconnect)
+    /// policy.
+    pub fn max_message_size(mut self, bytes: usize) -> Self {
+        self.config.max_message_size = Some(bytes);
+        self
+    }
+
+    /// Reconnect with a limit of `bytes` once a frame exceeded
+    /// [`max_message_size`](Self::max_message_size), instead of keeping it.
+    pub fn fallback_max_message_size(mut self, bytes: usize) -> Self {
+        self.config.fallback_max_message_size = Some(bytes);
+        self
+    }
+
+    /// Seed the PRNG behind sampling and backoff jitter, making both
+    /// reproducible, e.g. in tests.
+    ///
+    /// Every subscription of the client starts from the same seed. Seeded
+    /// from entropy by default.
+    pub fn rng_seed(mut self, seed: u64) -> Self {
+        self.config.rng_seed = Some(seed);
+        self
+    }
+
+    /// Offer `protocol` in the handshake's `Sec-WebSocket-Protocol` header,
+    /// as required by some gateways.
+    ///
+    /// Call it again to offer several, in order of preference. Once any is
+    /// offered the handshake fails unless the server accepts one of them.
+    pub fn subprotocol(mut self, protocol: impl Into<String>) -> Self {
+        self.config.subprotocols.push(protocol.into());
+        self
+    }
+
+    /// Log every frame sent and received at `trace` level, for protocol
+    /// troubleshooting.
+    ///
+    /// Each event carries the direction, frame kind and length, plus the full
+    /// payload if `with_payload` is set. Unlike [`on_message`](Self::on_message)
+    /// outbound frames such as subscribe requests and pings are covered too.
+    pub fn trace_frames(mut self, with_payload: bool) -> Self {
+        self.config.trace_frames = Some(with_payload);
+        self
+    }
+
+    /// Connect to `addr` instead of resolving the url's host, e.g. where system
+    /// DNS is unavailable.
+    ///
+    /// The url's host is still sent in the `Host` header and used for TLS
+    /// server name verification. Applies to every connection of the client,
+    /// except after [`Subscription::switch_endpoint`](crate::client::Subscription::switch_endpoint),
+    /// which resolves the new url's host.
+    pub fn resolve_to(mut self, addr: SocketAddr) -> Self {
+        self.config.target = Some(Target::Addr(addr));
+        self
+    }
+
+    /// Connect through the Unix domain socket at `path`, e.g. of a co-located
+    /// relay, instead of over TCP.
+    ///
+    /// The url still sets the handshake's `Host` header and path, and `wss://`
+    /// urls run TLS over the socket. Replaces any
+    /// [`resolve_to`](Self::resolve_to) address, and like it is dropped by
+    /// [`Subscription::switch_endpoint`](crate::client::Subscription::switch_endpoint).
+    /// Only available with the `unix` feature.
+    #[cfg(all(unix, feature = "unix"))]
+    pub fn unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.target = Some(Target::Unix(path.into()));
+        self
+    }
+
+    /// Inject artificial delays, frame loss and reconnects per `chaos`, to
+    /// exercise a consumer's resilience without a misbehaving server.
+    ///
+    /// Only available with the `testing` feature.
+    #[cfg(feature = "testing")]
+    pub fn chaos(mut self, chaos: ChaosConfig) -> Self {
+        self.config.chaos = Some(chaos);
+        self
+    }
+
+    /// Use a custom rustls config for `wss://` connections, e.g. to pin roots.
+    ///
+    /// Only available with the `rustls-tls` feature.
+    #[cfg(feature = "rustls-tls")]
+    pub fn tls_config(mut self, config: Arc<rustls::ClientConfig>) -> Self {
+        self.config.tls = Some(config);
+        self
+    }
+
+    /// Build the client
+    pub fn build(self) -> Client {
+        Client {
+            url: self.url,
+            config: self.config,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use std::collections::BTreeMap;
+
+/// What an endpoint reported supporting, see
+/// [`Client::capabilities`](crate::client::Client::capabilities)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// RPC modules and their versions as answered to `rpc_modules`, `None` if
+    /// the endpoint doesn't support discovery
+    pub modules: Option<BTreeMap<String, String>>,
+}
+
+impl Capabilities {
+    /// `true` if the endpoint answered discovery
+    pub fn is_known(&self) -> bool {
+        self.modules.is_some()
+    }
+
+    /// Whether the endpoint supports `module`, `None` if it didn't answer
+    /// discovery, in which case only the `newTxs` feed can be assumed
+    pub fn supports(&self, module: &str) -> Option<bool> {
+        self.modules
+            .as_ref()
+            .map(|modules| modules.contains_key(module))
+    }
+}
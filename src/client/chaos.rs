@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+/// Faults injected into every subscription of a client, for testing how
+/// consumers cope with latency, gaps and reconnects.
+///
+/// Each fault hits a random fraction of data frames, drawn from the
+/// subscription's PRNG, so runs are reproducible with
+/// [`ClientBuilder::rng_seed`](crate::client::ClientBuilder::rng_seed).
+/// Only available with the `testing` feature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChaosConfig {
+    pub(crate) delay: Option<(f64, Duration)>,
+    pub(crate) drop_rate: f64,
+    pub(crate) reconnect_rate: f64,
+}
+
+impl ChaosConfig {
+    /// Create a config injecting no faults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold a `rate` fraction of data frames for `delay` before handling them
+    pub fn delay_frames(mut self, rate: f64, delay: Duration) -> Self {
+        self.delay = Some((rate.clamp(0.0, 1.0), delay));
+        self
+    }
+
+    /// Discard a `rate` fraction of data frames, as if lost upstream
+    pub fn drop_frames(mut self, rate: f64) -> Self {
+        self.drop_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Fail the connection on a `rate` fraction of data frames, before
+    /// handling them, which is then reconnected per the client's
+    /// [`reconnect`](crate::client::ClientBuilder::reconnect) policy
+    pub fn force_reconnects(mut self, rate: f64) -> Self {
+        self.reconnect_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+}
@@ -0,0 +1,65 @@
+use prometheus::{IntCounter, IntGauge, Registry};
+
+/// Prometheus collectors shared by every subscription of a client
+#[derive(Clone)]
+pub(crate) struct Metrics {
+    pub(crate) txs_received: IntCounter,
+    pub(crate) reconnects: IntCounter,
+    pub(crate) parse_errors: IntCounter,
+    pub(crate) channel_depth: IntGauge,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Metrics {
+    /// Create the collectors and register them against `registry`
+    pub(crate) fn register(registry: &Registry) -> prometheus::Result<Self> {
+        let metrics = Self {
+            txs_received: IntCounter::new("txs_received_total", "Pending txs received")?,
+            reconnects: IntCounter::new(
+                "reconnects_total",
+                "Reconnections after the connection dropped",
+            )?,
+            parse_errors: IntCounter::new("parse_errors_total", "Messages that failed to parse")?,
+            channel_depth: IntGauge::new("channel_depth", "Txs waiting to be consumed")?,
+        };
+
+        registry.register(Box::new(metrics.txs_received.clone()))?;
+        registry.register(Box::new(metrics.reconnects.clone()))?;
+        registry.register(Box::new(metrics.parse_errors.clone()))?;
+        registry.register(Box::new(metrics.channel_depth.clone()))?;
+
+        Ok(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::stats::Stats;
+
+    #[test]
+    fn stats_update_registered_metrics() {
+        let registry = Registry::new();
+        let stats = Stats::with_metrics(Some(Metrics::register(&registry).unwrap()));
+
+        stats.record_tx();
+        stats.record_queued();
+        stats.record_parse_error();
+
+        let families = registry.gather();
+        let value = |name: &str| {
+            families
+                .iter()
+                .find(|family| family.get_name() == name)
+                .map(|family| family.get_metric()[0].get_counter().get_value())
+        };
+        assert_eq!(value("txs_received_total"), Some(1.0));
+        assert_eq!(value("parse_errors_total"), Some(1.0));
+        assert_eq!(value("reconnects_total"), Some(0.0));
+    }
+}
@@ -1,105 +1,382 @@
-use eyre::Result;
-use futures_util::{stream::SplitSink, SinkExt, StreamExt};
-use tokio::{net::TcpStream, sync::mpsc};
+use std::{
+    marker::PhantomData,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+use eyre::{eyre, Result};
+use futures_util::{
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
+use tokio::sync::{mpsc, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_tungstenite::{
-    connect_async, tungstenite::client::IntoClientRequest, tungstenite::Message, MaybeTlsStream,
-    WebSocketStream,
+    client_async_tls_with_config,
+    tungstenite::{
+        client::IntoClientRequest,
+        handshake::client::Request,
+        http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue},
+        protocol::WebSocketConfig,
+        Message,
+    },
+    Connector, MaybeTlsStream, WebSocketStream,
 };
 use url::Url;
 
-use crate::{json_rpc::notification::EdenItem, types::EdenPendingTx};
+use crate::{
+    json_rpc::{id::Id, notification::EdenItem, response::ResponsePayload},
+    sink::TxSink,
+};
+
+mod builder;
+mod capabilities;
+#[cfg(feature = "testing")]
+mod chaos;
+#[cfg(feature = "prometheus")]
+mod metrics;
+mod options;
+mod rate_limit;
+mod reconnect;
+mod rng;
+mod stats;
+mod subscription;
+mod task;
+mod transport;
+
+pub use builder::ClientBuilder;
+use builder::ClientConfig;
+pub use capabilities::Capabilities;
+#[cfg(feature = "testing")]
+pub use chaos::ChaosConfig;
+pub use options::{SubscriptionKind, SubscriptionOptions};
+use rate_limit::TokenBucket;
+pub use reconnect::{ReconnectEvent, ReconnectPolicy};
+use rng::FastRng;
+use stats::Stats;
+pub use stats::{StatsSnapshot, TxTypeCounts};
+use subscription::Ack;
+pub use subscription::{CloseOutcome, ConnectionState, Subscription, TerminationReason};
+use task::SubscriptionTask;
+use transport::Target;
+pub use transport::Transport;
 
 // declare type aliases
-pub type TungsteniteStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
-pub type Writer = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+pub type TungsteniteStream = WebSocketStream<MaybeTlsStream<Transport>>;
+pub type Writer = SplitSink<WebSocketStream<MaybeTlsStream<Transport>>, Message>;
+pub type Reader = SplitStream<WebSocketStream<MaybeTlsStream<Transport>>>;
+
+/// How long `health_check` waits for the pong
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long `capabilities` waits for the discovery response
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Default max time a single frame write may take
+const DEFAULT_WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Offers `subprotocols` in the handshake of `req`, if any
+pub(crate) fn with_subprotocols(mut req: Request, subprotocols: &[String]) -> Result<Request> {
+    if !subprotocols.is_empty() {
+        let offered = HeaderValue::from_str(&subprotocols.join(", "))?;
+        req.headers_mut().insert(SEC_WEBSOCKET_PROTOCOL, offered);
+    }
+
+    Ok(req)
+}
+
+/// Performs the WebSocket handshake for `req`, over a connection to `target`
+/// if set instead of resolving the request's host
+pub(crate) async fn connect_stream(
+    req: Request,
+    ws_config: Option<WebSocketConfig>,
+    connector: Option<Connector>,
+    target: Option<&Target>,
+) -> Result<TungsteniteStream> {
+    let transport = Transport::connect(req.uri(), target).await?;
+    let (stream, _) = client_async_tls_with_config(req, transport, ws_config, connector).await?;
+
+    Ok(stream)
+}
 
 /// Eden Mempool Client
 pub struct Client {
     pub(crate) url: Url,
+    pub(crate) config: ClientConfig,
 }
 
 impl Client {
     /// Initialize new client with eden agg mempool url
     pub fn new(wss: impl Into<Url>) -> Self {
-        Self { url: wss.into() }
+        ClientBuilder::new(wss).build()
+    }
+
+    /// Start building a client with non-default settings
+    pub fn builder(wss: impl Into<Url>) -> ClientBuilder {
+        ClientBuilder::new(wss)
+    }
+
+    /// Register subscription metrics against `registry`.
+    ///
+    /// Exports `txs_received_total`, `reconnects_total`, `parse_errors_total`
+    /// and `channel_depth`, aggregated over every subscription opened after
+    /// this call.
+    #[cfg(feature = "prometheus")]
+    pub fn register_metrics(&mut self, registry: &prometheus::Registry) -> Result<()> {
+        self.config.metrics = Some(metrics::Metrics::register(registry)?);
+        Ok(())
     }
 
-    // sends pending tx subscription msg
-    async fn subscribe_internal(stream: &mut Writer, params: &[&str]) -> Result<()> {
-        let params = serde_json::json!({
+    /// Verifies the endpoint speaks WebSocket without subscribing.
+    ///
+    /// Performs the handshake, waits for the server to answer a ping and
+    /// closes the connection. Returns the handshake latency.
+    pub async fn health_check(&self) -> Result<Duration> {
+        let req = self.url.clone().into_client_request()?;
+        let req = with_subprotocols(req, &self.config.subprotocols)?;
+
+        let start = Instant::now();
+        let mut stream = connect_stream(
+            req,
+            None,
+            self.config.connector(),
+            self.config.target.as_ref(),
+        )
+        .await?;
+        let handshake = start.elapsed();
+
+        let sent = Instant::now();
+        stream.send(Message::Ping(Vec::new())).await?;
+
+        tokio::time::timeout(HEALTH_CHECK_TIMEOUT, async {
+            while let Some(payload) = stream.next().await {
+                if let Message::Pong(_) = payload? {
+                    return eyre::Ok(());
+                }
+            }
+            Err(eyre!("connection closed before pong"))
+        })
+        .await
+        .map_err(|_| eyre!("no pong within {HEALTH_CHECK_TIMEOUT:?}"))??;
+        tracing::debug!(?handshake, rtt = ?sent.elapsed(), "Health check passed");
+
+        let _ = stream.close(None).await;
+
+        Ok(handshake)
+    }
+
+    /// Asks the endpoint which RPC modules it supports, without subscribing.
+    ///
+    /// Sends a standard `rpc_modules` request and closes the connection once
+    /// answered. Eden doesn't document a discovery method, so an error reply,
+    /// an unexpected result or no reply within 5 seconds yield unknown
+    /// [`Capabilities`] rather than an error. Only failing to connect errors.
+    pub async fn capabilities(&self) -> Result<Capabilities> {
+        let req = self.url.clone().into_client_request()?;
+        let req = with_subprotocols(req, &self.config.subprotocols)?;
+        let mut stream = connect_stream(
+            req,
+            None,
+            self.config.connector(),
+            self.config.target.as_ref(),
+        )
+        .await?;
+
+        let request = serde_json::json!({
             "jsonrpc": "2.0",
             "id": 1,
-            "method": "subscribe",
-            "params": params,
+            "method": "rpc_modules",
+            "params": [],
         });
+        stream.send(Message::Text(request.to_string())).await?;
 
-        let params_str = serde_json::to_string(&params)?;
+        let response = tokio::time::timeout(DISCOVERY_TIMEOUT, async {
+            while let Some(Ok(payload)) = stream.next().await {
+                let Message::Text(text) = payload else {
+                    continue;
+                };
+                match serde_json::from_str(&text) {
+                    Ok(EdenItem::Response(r)) if r.id == Id::Number(1) => return Some(r),
+                    _ => continue,
+                }
+            }
+            None
+        })
+        .await;
 
-        Ok(stream.send(Message::Text(params_str)).await?)
+        let modules = match response {
+            Ok(Some(r)) => match r.payload {
+                ResponsePayload::Success(result) => serde_json::from_str(result.get()).ok(),
+                ResponsePayload::Failure(e) => {
+                    tracing::debug!(error = %e, "Endpoint doesn't support discovery");
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(_) => {
+                tracing::debug!("No discovery response within {DISCOVERY_TIMEOUT:?}");
+                None
+            }
+        };
+
+        let _ = stream.close(None).await;
+
+        Ok(Capabilities { modules })
+    }
+
+    /// Subscribes and hands every tx to `sink` until the subscription ends.
+    ///
+    /// Dropped connections are reconnected per the client's
+    /// [`reconnect`](ClientBuilder::reconnect) policy, or a default
+    /// [`ReconnectPolicy`] retrying forever if none is set, so this only
+    /// returns once the feed stopped for good: with the reason it ended, or
+    /// an error if it failed. Txs queue in the subscription channel while
+    /// `sink` is busy.
+    pub async fn run_with_sink<S: TxSink>(&self, sink: S) -> Result<TerminationReason> {
+        let reconnect = self.config.reconnect.clone().unwrap_or_default();
+        let mut sub = self
+            .subscribe(SubscriptionOptions::default(), Some(reconnect))
+            .await?;
+        while let Some(tx) = sub.next().await {
+            sink.on_tx(tx).await;
+        }
+
+        match sub.termination_reason() {
+            Some(TerminationReason::Error(e)) => Err(eyre!(e)),
+            Some(reason) => Ok(reason),
+            None => Err(eyre!("subscription ended without a reason")),
+        }
     }
 
     /// subscribes and returns stream of `EdenPedningTx`
-    pub async fn subscribe_txs(&self) -> Result<UnboundedReceiverStream<EdenPendingTx>> {
+    pub async fn subscribe_txs(&self) -> Result<Subscription> {
+        self.subscribe_txs_with(SubscriptionOptions::default())
+            .await
+    }
+
+    /// subscribes with custom processing options and returns stream of `EdenPedningTx`
+    pub async fn subscribe_txs_with(&self, options: SubscriptionOptions) -> Result<Subscription> {
+        self.subscribe(options, self.config.reconnect.clone()).await
+    }
+
+    // spawns the subscription task, reconnecting per `reconnect` instead of the client's policy
+    async fn subscribe(
+        &self,
+        options: SubscriptionOptions,
+        reconnect: Option<ReconnectPolicy>,
+    ) -> Result<Subscription> {
+        if options.initial_snapshot {
+            return Err(eyre!(
+                "initial snapshot is unsupported: Eden exposes no method returning the pending pool"
+            ));
+        }
+
         let req = self.url.clone().into_client_request()?;
+        let req = with_subprotocols(req, &self.config.subprotocols)?;
         let (tx, rx) = mpsc::unbounded_channel();
+        let (commands, commands_rx) = mpsc::unbounded_channel();
+        let (responses, responses_rx) = if options.forward_responses {
+            let (tx, rx) = mpsc::unbounded_channel();
+            (Some(tx), Some(UnboundedReceiverStream::new(rx)))
+        } else {
+            (None, None)
+        };
+        let termination = Arc::new(OnceLock::new());
+        let (state, state_rx) = watch::channel(ConnectionState::Connecting);
+        let (ack, ack_rx) = watch::channel(Ack::Pending);
+        #[cfg(feature = "prometheus")]
+        let stats = Arc::new(Stats::with_metrics(self.config.metrics.clone()));
+        #[cfg(not(feature = "prometheus"))]
+        let stats = Arc::new(Stats::default());
 
-        tokio::spawn(async move {
-            let (stream, _) = connect_async(req.clone()).await?;
-            let (mut write, mut read) = stream.split();
-
-            // subsctibe to full pednings txs
-            Self::subscribe_internal(&mut write, &["newTxs"]).await?;
-
-            // handle stream data
-            while let Some(item) = read.next().await {
-                match item {
-                    Ok(payload) => match payload {
-                        Message::Text(text) => {
-                            // deserialize
-                            let item: EdenItem = serde_json::from_str(&text)?;
-
-                            // match if it is a `Notification` or `Response`
-                            match item {
-                                EdenItem::Response(r) => {
-                                    if r.payload.is_error() {
-                                        tracing::error!("Error in reponse: {:?}", r.payload);
-                                    }
-                                }
-                                EdenItem::Notification(n) => {
-                                    tx.send(n.result)?;
-                                }
-                            }
-                        }
-                        Message::Pong(pong_data) => {
-                            tracing::debug!("Received Pong");
-                            write.send(Message::Ping(pong_data)).await?;
-                        }
-                        Message::Ping(ping_data) => {
-                            tracing::debug!("Received Ping");
-                            write.send(Message::Pong(ping_data)).await?;
-                        }
-                        Message::Close(frame) => {
-                            if frame.is_some() {
-                                tracing::error!(?frame, "Received close frame with data");
-                            } else {
-                                tracing::error!("WS server has gone away");
-                            }
-                            return Err(eyre::eyre!("Stream has been closed"));
-                        }
-                        _ => {}
-                    },
-                    Err(e) => {
-                        tracing::error!(error = ?e, "Error in transaction stream");
-                        break;
-                    }
-                }
-            }
+        let mut task = SubscriptionTask {
+            req,
+            connector: self.config.connector(),
+            limiter: self.config.max_requests_per_sec.map(TokenBucket::new),
+            shedder: options.max_messages_per_sec.map(TokenBucket::new),
+            reconnect,
+            feeds: vec![SubscriptionKind::NewTxs],
+            options,
+            stats: stats.clone(),
+            rng: self
+                .config
+                .rng_seed
+                .map_or_else(FastRng::from_entropy, FastRng::seeded),
+            tx,
+            responses,
+            commands: commands_rx,
+            seq: 0,
+            label: self.config.label.clone(),
+            write_timeout: self.config.write_timeout.unwrap_or(DEFAULT_WRITE_TIMEOUT),
+            on_message: self.config.on_message.clone(),
+            keepalive: self.config.keepalive,
+            read_timeout: self.config.read_timeout,
+            target: self.config.target.clone(),
+            #[cfg(feature = "testing")]
+            chaos: self.config.chaos,
+            trace_frames: self.config.trace_frames,
+            subprotocols: self.config.subprotocols.clone(),
+            next_request_id: 0,
+            subscribe_id: None,
+            state,
+            ack,
+            parse_failures: 0,
+            ws_config: self.config.ws_config(),
+            fallback_max_message_size: self.config.fallback_max_message_size,
+        };
 
-            eyre::Ok(())
-        });
+        let reason = termination.clone();
+        let driver = async move {
+            let outcome = task
+                .run()
+                .await
+                .unwrap_or_else(|e| TerminationReason::Error(e.to_string()));
+            // record the reason before `task.tx` drops so it is visible once the stream ends
+            let _ = reason.set(outcome);
+            task.state.send_replace(ConnectionState::Closed);
+        };
+        #[cfg(all(tokio_unstable, feature = "console"))]
+        tokio::task::Builder::new()
+            .name("eden-subscription")
+            .spawn(driver)?;
+        #[cfg(not(all(tokio_unstable, feature = "console")))]
+        tokio::spawn(driver);
 
-        Ok(UnboundedReceiverStream::new(rx))
+        Ok(Subscription {
+            rx: UnboundedReceiverStream::new(rx),
+            termination,
+            stats,
+            commands,
+            label: self.config.label.clone(),
+            responses: responses_rx,
+            state: state_rx,
+            ack: ack_rx,
+            item: PhantomData,
+        })
+    }
+}
+
+impl TryFrom<&str> for Client {
+    type Error = eyre::Report;
+
+    /// Parse a `ws://` or `wss://` url into a client with default settings
+    fn try_from(wss: &str) -> Result<Self> {
+        let url = Url::parse(wss)?;
+        if !matches!(url.scheme(), "ws" | "wss") {
+            return Err(eyre!(
+                "unsupported url scheme `{}`, expected `ws` or `wss`",
+                url.scheme()
+            ));
+        }
+
+        Ok(Client::new(url))
+    }
+}
+
+impl FromStr for Client {
+    type Err = eyre::Report;
+
+    fn from_str(wss: &str) -> Result<Self> {
+        Client::try_from(wss)
     }
 }
 
@@ -108,7 +385,23 @@ mod tests {
 
     const MEMPOOL_WS: &str = "wss://speed-eu-west.edennetwork.io";
 
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use static_assertions::assert_impl_all;
+    use tokio::net::{TcpListener, TcpStream};
+
     use super::*;
+    use crate::test_utils::{notification, ws_server, TX};
+
+    assert_impl_all!(Client: Send, Sync);
+
+    struct Counter(Arc<AtomicUsize>);
+
+    impl TxSink for Counter {
+        async fn on_tx(&self, _: crate::types::EdenPendingTx) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
 
     #[tokio::test]
     async fn test_txs_subscription() {
@@ -120,4 +413,750 @@ mod tests {
 
         assert!(pedning_tx.is_some());
     }
+
+    #[tokio::test]
+    async fn close_is_acknowledged() {
+        let url = ws_server(|mut ws| async move {
+            // tungstenite replies to the close frame on the next read
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let sub = Client::new(url).subscribe_txs().await.unwrap();
+        let outcome = sub.close().await.unwrap();
+
+        assert!(outcome.acknowledged);
+        assert_eq!(outcome.code, Some(1000));
+    }
+
+    #[tokio::test]
+    async fn initial_snapshot_is_unsupported() {
+        let client = Client::new(Url::parse("ws://127.0.0.1:1").unwrap());
+        let options = SubscriptionOptions::new().initial_snapshot(true);
+
+        let err = client.subscribe_txs_with(options).await.unwrap_err();
+        assert!(err.to_string().contains("unsupported"));
+    }
+
+    #[tokio::test]
+    async fn health_check_measures_handshake() {
+        let url = ws_server(|mut ws| async move {
+            // tungstenite answers pings while reading
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let latency = Client::new(url).health_check().await.unwrap();
+        assert!(latency < HEALTH_CHECK_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn discovers_capabilities() {
+        let url = ws_server(|mut ws| async move {
+            let Some(Ok(Message::Text(request))) = ws.next().await else {
+                return;
+            };
+            let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+            assert_eq!(request["method"], "rpc_modules");
+
+            let modules = r#"{"jsonrpc":"2.0","result":{"eth":"1.0","eden":"1.0"},"id":1}"#;
+            ws.send(Message::Text(modules.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let capabilities = Client::new(url).capabilities().await.unwrap();
+        assert!(capabilities.is_known());
+        assert_eq!(capabilities.supports("eden"), Some(true));
+        assert_eq!(capabilities.supports("txpool"), Some(false));
+    }
+
+    #[tokio::test]
+    async fn unsupported_discovery_falls_back() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            let error =
+                r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"method not found"},"id":1}"#;
+            ws.send(Message::Text(error.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let capabilities = Client::new(url).capabilities().await.unwrap();
+        assert!(!capabilities.is_known());
+        assert_eq!(capabilities.supports("eden"), None);
+    }
+
+    #[tokio::test]
+    async fn health_check_fails_without_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let url = Url::parse(&format!("ws://{addr}")).unwrap();
+        assert!(Client::new(url).health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resubscribes_after_reconnect() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let url = ws_server(move |mut ws| {
+            let connection = connections.fetch_add(1, Ordering::SeqCst);
+            async move {
+                // wait for the subscribe request before streaming
+                let _ = ws.next().await;
+                ws.send(Message::Text(notification(TX))).await.unwrap();
+
+                // drop the first connection of each subscription without a close frame
+                if connection >= 2 {
+                    while let Some(Ok(_)) = ws.next().await {}
+                }
+            }
+        })
+        .await;
+
+        let client = Client::builder(url)
+            .reconnect(ReconnectPolicy::new().initial_backoff(Duration::from_millis(10)))
+            .build();
+        let mut first = client.subscribe_txs().await.unwrap();
+        let mut second = client.subscribe_txs().await.unwrap();
+
+        for sub in [&mut first, &mut second] {
+            assert!(sub.next().await.is_some());
+            assert!(sub.next().await.is_some());
+            assert!(sub.stats().reconnects >= 1);
+
+            let history = sub.reconnect_history();
+            assert!(!history.is_empty());
+            assert_eq!(history[0].reason, TerminationReason::ConnectionLost);
+            assert_eq!(history[0].attempt, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_budget_gives_up_on_flapping_endpoint() {
+        // every connection subscribes, streams one tx and drops
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+        })
+        .await;
+
+        let policy = ReconnectPolicy::new()
+            .initial_backoff(Duration::from_millis(10))
+            .retry_budget(2, Duration::from_secs(3600));
+        let client = Client::builder(url).reconnect(policy).build();
+        let mut sub = client.subscribe_txs().await.unwrap();
+
+        // the initial connection plus the two budgeted reconnects
+        assert_eq!(sub.by_ref().count().await, 3);
+        assert_eq!(sub.stats().retry_budget, Some(0));
+        assert_eq!(sub.stats().reconnects, 2);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn chaos_drops_frames() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            for _ in 0..3 {
+                ws.send(Message::Text(notification(TX))).await.unwrap();
+            }
+            ws.close(None).await.unwrap();
+        })
+        .await;
+
+        let chaos = ChaosConfig::new().drop_frames(1.0);
+        let client = Client::builder(url).chaos(chaos).build();
+        let mut sub = client.subscribe_txs().await.unwrap();
+
+        assert!(sub.next().await.is_none());
+        assert_eq!(sub.stats().txs_received, 0);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn chaos_forces_reconnects() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let chaos = ChaosConfig::new().force_reconnects(1.0);
+        let client = Client::builder(url)
+            .reconnect(ReconnectPolicy::new().initial_backoff(Duration::from_millis(10)))
+            .chaos(chaos)
+            .build();
+        let sub = client.subscribe_txs().await.unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while sub.stats().reconnects < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(sub.stats().txs_received, 0);
+    }
+
+    #[tokio::test]
+    async fn idle_subscription_terminates() {
+        let url = ws_server(|mut ws| async move {
+            // accept the subscription but never stream
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let options = SubscriptionOptions::new().idle_timeout(Duration::from_millis(50));
+        let mut sub = Client::new(url).subscribe_txs_with(options).await.unwrap();
+
+        assert!(sub.next().await.is_none());
+        assert_eq!(sub.termination_reason(), Some(TerminationReason::Idle));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_consecutive_parse_errors() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text("not json".into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let client = Client::builder(url)
+            .reconnect(ReconnectPolicy::new().initial_backoff(Duration::from_millis(10)))
+            .build();
+        let options = SubscriptionOptions::new().max_consecutive_parse_errors(2);
+        let mut sub = client.subscribe_txs_with(options).await.unwrap();
+
+        assert!(sub.next().await.is_none());
+        assert_eq!(sub.stats().parse_errors, 2);
+        assert_eq!(sub.stats().reconnects, 1);
+        assert!(sub
+            .close_reason()
+            .unwrap()
+            .contains("2 consecutive messages failed to parse"));
+    }
+
+    #[tokio::test]
+    async fn reconnects_with_fallback_message_size() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let client = Client::builder(url)
+            .reconnect(ReconnectPolicy::new().initial_backoff(Duration::from_millis(10)))
+            .max_message_size(256)
+            .fallback_max_message_size(1 << 20)
+            .build();
+        let mut sub = client.subscribe_txs().await.unwrap();
+
+        assert!(sub.next().await.is_some());
+        assert_eq!(sub.stats().oversized_frames, 1);
+        assert_eq!(sub.stats().reconnects, 1);
+    }
+
+    #[tokio::test]
+    async fn seeded_sampling_is_reproducible() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            for _ in 0..20 {
+                ws.send(Message::Text(notification(TX))).await.unwrap();
+            }
+        })
+        .await;
+
+        let mut rng = FastRng::seeded(7);
+        let expected = (0..20).filter(|_| rng.next_f64() < 0.5).count();
+
+        let client = Client::builder(url).rng_seed(7).build();
+        let options = SubscriptionOptions::new().sample_rate(0.5);
+        let sub = client.subscribe_txs_with(options).await.unwrap();
+        let stats = sub.stats.clone();
+
+        assert_eq!(sub.count().await, expected);
+        assert_eq!(stats.snapshot().sampled_out, (20 - expected) as u64);
+    }
+
+    #[tokio::test]
+    async fn pings_do_not_reset_idle_timeout() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            loop {
+                if ws.send(Message::Ping(Vec::new())).await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+
+        let options = SubscriptionOptions::new().idle_timeout(Duration::from_millis(100));
+        let mut sub = Client::new(url).subscribe_txs_with(options).await.unwrap();
+
+        assert!(sub.next().await.is_none());
+        assert_eq!(sub.termination_reason(), Some(TerminationReason::Idle));
+    }
+
+    #[tokio::test]
+    async fn silent_socket_reconnects() {
+        let url = ws_server(|mut ws| async move {
+            // accept the subscription but never send a frame
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let client = Client::builder(url)
+            .read_timeout(Duration::from_millis(50))
+            .reconnect(ReconnectPolicy::new().initial_backoff(Duration::from_millis(10)))
+            .build();
+        let sub = client.subscribe_txs().await.unwrap();
+
+        while sub.stats().reconnects == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let history = sub.reconnect_history();
+        assert!(
+            matches!(&history[0].reason, TerminationReason::Error(e) if e.contains("no frame received"))
+        );
+    }
+
+    #[tokio::test]
+    async fn connects_to_resolved_address() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+        let addr = url.socket_addrs(|| None).unwrap()[0];
+
+        // the host doesn't resolve, only the pinned address is reachable
+        let unresolvable = Url::parse(&format!("ws://eden.invalid:{}", addr.port())).unwrap();
+        let client = Client::builder(unresolvable).resolve_to(addr).build();
+
+        assert!(client.health_check().await.is_ok());
+        let mut sub = client.subscribe_txs().await.unwrap();
+        assert!(sub.next().await.is_some());
+    }
+
+    #[cfg(all(unix, feature = "unix"))]
+    #[tokio::test]
+    async fn connects_over_unix_socket() {
+        let path = std::env::temp_dir().join(format!("eden-rs-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+
+        let url = Url::parse("ws://localhost/").unwrap();
+        let client = Client::builder(url).unix_socket(&path).build();
+        let mut sub = client.subscribe_txs().await.unwrap();
+        assert!(sub.next().await.is_some());
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn sheds_inbound_floods() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            for _ in 0..10 {
+                ws.send(Message::Text(notification(TX))).await.unwrap();
+            }
+        })
+        .await;
+
+        let options = SubscriptionOptions::new().max_messages_per_sec(3);
+        let sub = Client::new(url).subscribe_txs_with(options).await.unwrap();
+        let stats = sub.stats.clone();
+
+        // the burst capacity passes, the rest of the flood is shed
+        assert_eq!(sub.count().await, 3);
+        assert_eq!(stats.snapshot().shed, 7);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_handle_stops_cleanly() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            while ws.send(Message::Text(notification(TX))).await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await;
+
+        let mut sub = Client::new(url).subscribe_txs().await.unwrap();
+        let termination = sub.termination.clone();
+        let mut state = sub.state_changes();
+
+        assert!(sub.next().await.is_some());
+        drop(sub);
+
+        state
+            .wait_for(|s| *s == ConnectionState::Closed)
+            .await
+            .unwrap();
+        assert_eq!(termination.get(), Some(&TerminationReason::Dropped));
+    }
+
+    #[tokio::test]
+    async fn dropping_the_handle_closes_a_silent_feed() {
+        let (closed, mut closes) = mpsc::unbounded_channel();
+        let url = ws_server(move |mut ws| {
+            let closed = closed.clone();
+            async move {
+                let _ = ws.next().await;
+                // never sends a tx, so no failed delivery notices the drop
+                while let Some(Ok(msg)) = ws.next().await {
+                    if msg.is_close() {
+                        let _ = closed.send(());
+                    }
+                }
+            }
+        })
+        .await;
+
+        let sub = Client::new(url).subscribe_txs().await.unwrap();
+        let termination = sub.termination.clone();
+        let mut state = sub.state_changes();
+        state
+            .wait_for(|s| *s == ConnectionState::Subscribed)
+            .await
+            .unwrap();
+        drop(sub);
+
+        tokio::time::timeout(Duration::from_secs(5), closes.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        state
+            .wait_for(|s| *s == ConnectionState::Closed)
+            .await
+            .unwrap();
+        assert_eq!(termination.get(), Some(&TerminationReason::Dropped));
+    }
+
+    #[test]
+    fn offers_subprotocols() {
+        let req = "ws://localhost".into_client_request().unwrap();
+        let protocols = ["json".to_owned(), "jsonrpc".to_owned()];
+
+        let req = with_subprotocols(req, &protocols).unwrap();
+        assert_eq!(req.headers()[SEC_WEBSOCKET_PROTOCOL], "json, jsonrpc");
+    }
+
+    #[tokio::test]
+    async fn unaccepted_subprotocol_fails_handshake() {
+        // the test server accepts without negotiating any subprotocol
+        let url =
+            ws_server(|mut ws| async move { while let Some(Ok(_)) = ws.next().await {} }).await;
+
+        let client = Client::builder(url).subprotocol("json").build();
+        assert!(client.health_check().await.is_err());
+        assert!(client.subscribe_txs().await.unwrap().next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn switches_endpoint() {
+        let serve_one = |mut ws: WebSocketStream<TcpStream>| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        };
+        let old = ws_server(serve_one).await;
+        let new = ws_server(serve_one).await;
+
+        let mut sub = Client::new(old).subscribe_txs().await.unwrap();
+        assert!(sub.next().await.is_some());
+
+        sub.switch_endpoint(new).await.unwrap();
+        assert!(sub.next().await.is_some());
+
+        let dead = Url::parse("ws://127.0.0.1:1").unwrap();
+        assert!(sub.switch_endpoint(dead).await.is_err());
+        assert!(sub.termination_reason().is_none());
+    }
+
+    #[tokio::test]
+    async fn forwards_responses() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            let ack = r#"{"jsonrpc":"2.0","result":4815270595554998,"id":1}"#;
+            ws.send(Message::Text(ack.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let client = Client::new(url);
+        let options = SubscriptionOptions::new().forward_responses(true);
+        let mut sub = client.subscribe_txs_with(options).await.unwrap();
+
+        let mut responses = sub.responses().unwrap();
+        let ack = responses.next().await.unwrap();
+        assert_eq!(ack.id, crate::json_rpc::id::Id::Number(1));
+        assert!(ack.payload.is_success());
+        assert!(sub.responses().is_none());
+    }
+
+    #[tokio::test]
+    async fn waits_for_subscription_ack() {
+        let acking = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            let ack = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+            ws.send(Message::Text(ack.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+        let rejecting = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            let error =
+                r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"unknown feed"},"id":1}"#;
+            ws.send(Message::Text(error.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let sub = Client::new(acking).subscribe_txs().await.unwrap();
+        sub.wait_until_subscribed().await.unwrap();
+
+        let sub = Client::new(rejecting).subscribe_txs().await.unwrap();
+        let err = sub.wait_until_subscribed().await.unwrap_err();
+        assert!(err.to_string().contains("unknown feed"));
+    }
+
+    #[tokio::test]
+    async fn acks_match_the_configured_request_id() {
+        let url = ws_server(|mut ws| async move {
+            let Some(Ok(Message::Text(request))) = ws.next().await else {
+                return;
+            };
+            let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+            assert_eq!(request["id"], "probe-1");
+
+            // an ack for another request doesn't confirm the subscription
+            let other = r#"{"jsonrpc":"2.0","error":{"code":-1,"message":"other"},"id":1}"#;
+            ws.send(Message::Text(other.into())).await.unwrap();
+            let ack = r#"{"jsonrpc":"2.0","result":"0x1","id":"probe-1"}"#;
+            ws.send(Message::Text(ack.into())).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let id = crate::json_rpc::id::Id::String("probe-1".into());
+        let options = SubscriptionOptions::new().request_id(id);
+        let sub = Client::new(url).subscribe_txs_with(options).await.unwrap();
+
+        sub.wait_until_subscribed().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn attaches_raw_encoding() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let options = SubscriptionOptions::new().raw_encoding(true);
+        let sub = Client::new(url).subscribe_txs_with(options).await.unwrap();
+        let mut sub = sub.raw();
+
+        let item = sub.next().await.unwrap();
+        assert_eq!(item.raw, Some(item.tx.rlp().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn reassembles_fragmented_notifications() {
+        use tokio_tungstenite::tungstenite::protocol::frame::{
+            coding::{Data, OpCode},
+            Frame,
+        };
+
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            let payload = notification(TX).into_bytes();
+            let mut chunks = payload.chunks(64).peekable();
+            let mut opcode = OpCode::Data(Data::Text);
+            while let Some(chunk) = chunks.next() {
+                let frame = Frame::message(chunk.to_vec(), opcode, chunks.peek().is_none());
+                ws.send(Message::Frame(frame)).await.unwrap();
+                opcode = OpCode::Data(Data::Continue);
+            }
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let mut sub = Client::new(url).subscribe_txs().await.unwrap();
+        assert!(sub.next().await.is_some());
+        assert_eq!(sub.stats().parse_errors, 0);
+    }
+
+    #[tokio::test]
+    async fn runs_with_sink() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            for _ in 0..3 {
+                ws.send(Message::Text(notification(TX))).await.unwrap();
+            }
+            ws.close(None).await.unwrap();
+        })
+        .await;
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let reason = Client::builder(url)
+            .reconnect(ReconnectPolicy::new().max_retries(0))
+            .build()
+            .run_with_sink(Counter(count.clone()))
+            .await
+            .unwrap();
+
+        assert_eq!(reason, TerminationReason::ServerClosed);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn sink_survives_dropped_connections() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let url = ws_server(move |mut ws| {
+            let connection = connections.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let _ = ws.next().await;
+                ws.send(Message::Text(notification(TX))).await.unwrap();
+
+                // drop the first connection without a close frame
+                if connection >= 1 {
+                    while let Some(Ok(_)) = ws.next().await {}
+                }
+            }
+        })
+        .await;
+
+        // no reconnect policy configured on the client
+        let client = Client::new(url);
+        let count = Arc::new(AtomicUsize::new(0));
+        let run = client.run_with_sink(Counter(count.clone()));
+        let received = async {
+            while count.load(Ordering::SeqCst) < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::select! {
+                reason = run => panic!("sink stopped after a dropped connection: {reason:?}"),
+                _ = received => {}
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn on_message_sees_every_frame() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let frames = Arc::new(AtomicUsize::new(0));
+        let seen = frames.clone();
+        let client = Client::builder(url)
+            .on_message(move |msg| {
+                if msg.is_text() {
+                    seen.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .build();
+
+        let mut sub = client.subscribe_txs().await.unwrap();
+        assert!(sub.next().await.is_some());
+        assert_eq!(frames.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn reports_state_changes() {
+        let url =
+            ws_server(|mut ws| async move { while let Some(Ok(_)) = ws.next().await {} }).await;
+
+        let sub = Client::new(url).subscribe_txs().await.unwrap();
+        let mut state = sub.state_changes();
+
+        state
+            .wait_for(|s| *s == ConnectionState::Subscribed)
+            .await
+            .unwrap();
+        sub.close().await.unwrap();
+        state
+            .wait_for(|s| *s == ConnectionState::Closed)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn sends_keepalive_pings() {
+        let (pinged, mut pings) = mpsc::unbounded_channel();
+        let url = ws_server(move |mut ws| {
+            let pinged = pinged.clone();
+            async move {
+                while let Some(Ok(msg)) = ws.next().await {
+                    if msg.is_ping() {
+                        let _ = pinged.send(());
+                    }
+                }
+            }
+        })
+        .await;
+
+        let client = Client::builder(url)
+            .keepalive(Duration::from_millis(20))
+            .build();
+        let _sub = client.subscribe_txs().await.unwrap();
+
+        for _ in 0..2 {
+            pings.recv().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn items_carry_endpoint_label() {
+        let url = ws_server(|mut ws| async move {
+            let _ = ws.next().await;
+            ws.send(Message::Text(notification(TX))).await.unwrap();
+            while let Some(Ok(_)) = ws.next().await {}
+        })
+        .await;
+
+        let client = Client::builder(url).label("eu-west").build();
+        let sub = client.subscribe_txs().await.unwrap();
+        assert_eq!(sub.label(), Some("eu-west"));
+
+        let item = sub.sequenced().next().await.unwrap();
+        assert_eq!(item.label.as_deref(), Some("eu-west"));
+    }
+
+    #[test]
+    fn client_from_str() {
+        let client = Client::try_from(MEMPOOL_WS).unwrap();
+        assert_eq!(client.url.as_str(), "wss://speed-eu-west.edennetwork.io/");
+        assert!("ws://127.0.0.1:8546".parse::<Client>().is_ok());
+
+        assert!(Client::try_from("not a url").is_err());
+        assert!("https://speed-eu-west.edennetwork.io"
+            .parse::<Client>()
+            .is_err());
+    }
 }
@@ -0,0 +1,278 @@
+use std::{num::NonZeroU32, time::Duration};
+
+use ethers_core::types::U256;
+
+use crate::{json_rpc::id::Id, types::EdenPendingTx};
+
+/// Feed a subscription listens to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SubscriptionKind {
+    /// Full pending txs
+    NewTxs,
+}
+
+impl SubscriptionKind {
+    /// Name of the feed in the `subscribe` request params
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionKind::NewTxs => "newTxs",
+        }
+    }
+}
+
+/// Per-subscription processing options
+#[derive(Debug, Clone, Default)]
+pub struct SubscriptionOptions {
+    pub(crate) fill_missing_from: bool,
+    pub(crate) min_effective_gas_price: Option<U256>,
+    pub(crate) base_fee: Option<U256>,
+    pub(crate) sample_rate: Option<f64>,
+    pub(crate) initial_snapshot: bool,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) only_contract_creations: bool,
+    pub(crate) forward_responses: bool,
+    pub(crate) expected_method: Option<String>,
+    pub(crate) max_consecutive_parse_errors: Option<u32>,
+    pub(crate) validate_signatures: bool,
+    pub(crate) max_messages_per_sec: Option<NonZeroU32>,
+    pub(crate) request_id: Option<Id>,
+    pub(crate) raw_encoding: bool,
+    pub(crate) selector_allowlist: Option<Vec<[u8; 4]>>,
+}
+
+impl SubscriptionOptions {
+    /// Create options with every processing step disabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recover the signer for txs whose `from` is `Address::zero()`.
+    ///
+    /// Txs where recovery fails are still delivered with a zero `from` and
+    /// counted in [`StatsSnapshot::unrecovered_senders`](crate::client::StatsSnapshot).
+    pub fn fill_missing_from(mut self, enabled: bool) -> Self {
+        self.fill_missing_from = enabled;
+        self
+    }
+
+    /// Drop txs paying less than `min` per gas before they reach the channel.
+    ///
+    /// The effective gas price is computed against [`base_fee`](Self::base_fee)
+    /// when set. Otherwise EIP-1559 txs are judged by their `max_fee_per_gas`,
+    /// the most they could ever pay. Dropped txs are counted in
+    /// [`StatsSnapshot::filtered_out`](crate::client::StatsSnapshot).
+    pub fn min_effective_gas_price(mut self, min: U256) -> Self {
+        self.min_effective_gas_price = Some(min);
+        self
+    }
+
+    /// Reference base fee for [`min_effective_gas_price`](Self::min_effective_gas_price)
+    pub fn base_fee(mut self, base_fee: U256) -> Self {
+        self.base_fee = Some(base_fee);
+        self
+    }
+
+    /// Deliver only a random `rate` fraction of txs, e.g. `0.1` for 1 in 10.
+    ///
+    /// Sampling happens after parsing and after the other filters, so it cuts
+    /// downstream load but not parsing cost. Sampled-out txs are counted in
+    /// [`StatsSnapshot::sampled_out`](crate::client::StatsSnapshot). The rate
+    /// is clamped to `[0, 1]`.
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = Some(rate.clamp(0.0, 1.0));
+        self
+    }
+
+    /// Emit the current pending pool, flagged as [`Sequenced::snapshot`], before live txs.
+    ///
+    /// Eden's `newTxs` feed only streams deltas and exposes no method returning
+    /// the pending pool, so subscribing with this enabled currently fails with
+    /// an unsupported error.
+    ///
+    /// [`Sequenced::snapshot`]: crate::types::Sequenced::snapshot
+    pub fn initial_snapshot(mut self, enabled: bool) -> Self {
+        self.initial_snapshot = enabled;
+        self
+    }
+
+    /// Close the subscription if no data frame arrives for `timeout`.
+    ///
+    /// Only txs and responses reset the timer, pings and pongs don't. The
+    /// stream then ends with [`TerminationReason::Idle`](crate::client::TerminationReason::Idle)
+    /// and is not reconnected. See `ClientBuilder::read_timeout` to reconnect
+    /// dead sockets instead.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Drop every tx with a `to` address, keeping only contract deployments.
+    ///
+    /// Dropped txs are counted in
+    /// [`StatsSnapshot::filtered_out`](crate::client::StatsSnapshot).
+    pub fn only_contract_creations(mut self, enabled: bool) -> Self {
+        self.only_contract_creations = enabled;
+        self
+    }
+
+    /// Deliver only txs calling one of `selectors`, e.g. specific DEX or
+    /// bridge functions.
+    ///
+    /// Txs without calldata, including plain transfers, or with any other
+    /// [`selector`](EdenPendingTx::selector) are dropped and counted in
+    /// [`StatsSnapshot::filtered_out`](crate::client::StatsSnapshot).
+    pub fn selector_allowlist(mut self, selectors: Vec<[u8; 4]>) -> Self {
+        self.selector_allowlist = Some(selectors);
+        self
+    }
+
+    /// Shed txs arriving faster than `max` per second, protecting the consumer
+    /// from inbound floods.
+    ///
+    /// Txs passing the filters and sampling take a token from a bucket
+    /// holding up to `max` tokens, refilled continuously at `max` per second,
+    /// so bursts of up to one second's worth pass. Txs finding the bucket
+    /// empty are dropped instead of queued and counted in
+    /// [`StatsSnapshot::shed`](crate::client::StatsSnapshot::shed). `0`
+    /// disables shedding, which is the default.
+    pub fn max_messages_per_sec(mut self, max: u32) -> Self {
+        self.max_messages_per_sec = NonZeroU32::new(max);
+        self
+    }
+
+    /// Drop txs whose signature values are malformed, see
+    /// [`EdenPendingTx::has_valid_signature`].
+    ///
+    /// Dropped txs are counted in
+    /// [`StatsSnapshot::invalid_signatures`](crate::client::StatsSnapshot::invalid_signatures).
+    /// Off by default.
+    pub fn validate_signatures(mut self, enabled: bool) -> Self {
+        self.validate_signatures = enabled;
+        self
+    }
+
+    /// Send every subscribe request with `id`, e.g. to correlate it with the
+    /// server's logs.
+    ///
+    /// The ack awaited by [`Subscription::wait_until_subscribed`](crate::client::Subscription::wait_until_subscribed)
+    /// is matched against it. Requests are numbered from `1` by default,
+    /// counting up across reconnects.
+    pub fn request_id(mut self, id: Id) -> Self {
+        self.request_id = Some(id);
+        self
+    }
+
+    /// Attach the signed encoding to every delivered tx, see
+    /// [`Subscription::raw`](crate::client::Subscription::raw).
+    ///
+    /// The encoding is computed once in the read loop, after filtering and
+    /// sampling, so rebroadcasting consumers don't redo it per tx. Off by
+    /// default, in which case nothing is encoded.
+    pub fn raw_encoding(mut self, enabled: bool) -> Self {
+        self.raw_encoding = enabled;
+        self
+    }
+
+    /// Forward JSON-RPC responses, e.g. subscription acks, to
+    /// [`Subscription::responses`](crate::client::Subscription::responses).
+    ///
+    /// Off by default, in which case responses are only logged when they
+    /// carry an error.
+    pub fn forward_responses(mut self, enabled: bool) -> Self {
+        self.forward_responses = enabled;
+        self
+    }
+
+    /// Reject notifications whose `method` isn't `method`, e.g.
+    /// `"eth_subscription"` for Ethereum-style relays.
+    ///
+    /// Any method is accepted by default. Rejected messages count as parse
+    /// errors.
+    pub fn expected_method(mut self, method: impl Into<String>) -> Self {
+        self.expected_method = Some(method.into());
+        self
+    }
+
+    /// Terminate the subscription once `limit` messages in a row fail to
+    /// parse, instead of reconnecting after each of them.
+    ///
+    /// The failure is exposed through
+    /// [`Subscription::close_reason`](crate::client::Subscription::close_reason).
+    pub fn max_consecutive_parse_errors(mut self, limit: u32) -> Self {
+        self.max_consecutive_parse_errors = Some(limit);
+        self
+    }
+
+    /// `true` if `tx` passes every configured filter
+    pub(crate) fn accepts(&self, tx: &EdenPendingTx) -> bool {
+        if self.only_contract_creations && tx.to.is_some() {
+            return false;
+        }
+
+        if let Some(allowlist) = &self.selector_allowlist {
+            match tx.selector() {
+                Some(selector) if allowlist.contains(selector) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(min) = self.min_effective_gas_price {
+            // a saturating max base fee makes 1559 txs pay their full fee cap
+            let base_fee = self.base_fee.unwrap_or(U256::MAX);
+            if tx.effective_gas_price(base_fee) < min {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    #[test]
+    fn min_effective_gas_price_filter() {
+        // sample tx caps at 53 gwei with a 0.05 gwei tip
+        let tx = sample_tx();
+        let gwei = U256::exp10(9);
+
+        let by_fee_cap = SubscriptionOptions::new().min_effective_gas_price(gwei * 50);
+        assert!(by_fee_cap.accepts(&tx));
+
+        let with_base_fee = by_fee_cap.base_fee(gwei * 20);
+        assert!(!with_base_fee.accepts(&tx));
+
+        assert!(SubscriptionOptions::new().accepts(&tx));
+    }
+
+    #[test]
+    fn only_contract_creations_filter() {
+        let options = SubscriptionOptions::new().only_contract_creations(true);
+
+        let mut tx = sample_tx();
+        assert!(!options.accepts(&tx));
+
+        tx.to = None;
+        assert!(options.accepts(&tx));
+    }
+
+    #[test]
+    fn selector_allowlist_filter() {
+        let transfer = [0xa9, 0x05, 0x9c, 0xbb];
+        let options = SubscriptionOptions::new().selector_allowlist(vec![transfer]);
+
+        let mut tx = sample_tx();
+        tx.data = [transfer.as_slice(), &[0; 64]].concat().into();
+        assert!(options.accepts(&tx));
+
+        tx.data = vec![0x09, 0x5e, 0xa7, 0xb3].into();
+        assert!(!options.accepts(&tx));
+
+        tx.data = Default::default();
+        assert!(!options.accepts(&tx));
+    }
+}
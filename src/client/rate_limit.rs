@@ -0,0 +1,71 @@
+use std::{num::NonZeroU32, time::Duration};
+
+use tokio::time::Instant;
+
+/// Token bucket refilled continuously at `rate` tokens per second.
+///
+/// The bucket holds at most `rate` tokens, so bursts are capped to one
+/// second's worth of requests.
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(rate: NonZeroU32) -> Self {
+        let rate = f64::from(rate.get());
+        Self {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+    }
+
+    /// Takes a token if one is available.
+    pub(crate) fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Waits until a token is available and takes it.
+    pub(crate) async fn acquire(&mut self) {
+        while !self.try_acquire() {
+            let missing = 1.0 - self.tokens;
+            tokio::time::sleep(Duration::from_secs_f64(missing / self.rate)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn queues_requests_over_limit() {
+        let mut bucket = TokenBucket::new(NonZeroU32::new(2).unwrap());
+        let start = Instant::now();
+
+        // burst capacity is served immediately
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        // next request waits for a refill instead of being dropped
+        bucket.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::{Duration, SystemTime};
+
+use crate::client::{rng::FastRng, TerminationReason};
+
+/// A reconnect performed by a subscription, see
+/// [`Subscription::reconnect_history`](crate::client::Subscription::reconnect_history)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectEvent {
+    /// When the connection ended
+    pub at: SystemTime,
+    /// Why the connection ended
+    pub reason: TerminationReason,
+    /// Delay waited before reconnecting
+    pub backoff: Duration,
+    /// Consecutive failed attempt number, starting at `1`
+    pub attempt: u32,
+}
+
+/// Reconnect behaviour after a subscription's connection drops
+///
+/// The backoff starts at `initial_backoff` and doubles with every consecutive
+/// failed attempt up to `max_backoff`. The attempt counter resets once a
+/// connection is re-established and resubscribed, an optional
+/// [`retry_budget`](Self::retry_budget) only refills after a healthy run.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    pub(crate) max_retries: Option<u32>,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) jitter: bool,
+    pub(crate) history_len: usize,
+    pub(crate) retry_budget: Option<(u32, Duration)>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: None,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: false,
+            history_len: 32,
+            retry_budget: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Create policy retrying forever with a 500ms to 30s backoff
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Give up after `retries` consecutive failed attempts
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+
+    /// Delay before the first retry
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Upper bound of the delay between retries
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Sleep a random duration between zero and the computed backoff.
+    ///
+    /// This "full jitter" spreads out reconnects of many clients dropped at
+    /// the same time, at the cost of occasionally retrying sooner.
+    pub fn jitter(mut self, enabled: bool) -> Self {
+        self.jitter = enabled;
+        self
+    }
+
+    /// Number of recent reconnects kept in the subscription's history, 32 by default
+    pub fn history_len(mut self, len: usize) -> Self {
+        self.history_len = len;
+        self
+    }
+
+    /// Allow `retries` reconnects in total, refilled once a connection stays
+    /// subscribed for `refill_after`.
+    ///
+    /// Unlike [`max_retries`](Self::max_retries) reconnects that succeed
+    /// still spend the budget, so a flapping endpoint is given up on while a
+    /// connection that ran for hours before a blip starts over with the full
+    /// budget. The remaining budget is exposed in
+    /// [`StatsSnapshot::retry_budget`](crate::client::StatsSnapshot::retry_budget).
+    pub fn retry_budget(mut self, retries: u32, refill_after: Duration) -> Self {
+        self.retry_budget = Some((retries, refill_after));
+        self
+    }
+
+    /// Fresh budget of this policy, `None` if it has none
+    pub(crate) fn budget(&self) -> Option<RetryBudget> {
+        self.retry_budget
+            .map(|(retries, refill_after)| RetryBudget {
+                left: retries,
+                full: retries,
+                refill_after,
+            })
+    }
+
+    /// Delay before retry number `attempt`, starting at `0`
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+
+    /// Delay to actually sleep before retry number `attempt`, jittered if enabled
+    pub(crate) fn delay(&self, attempt: u32, rng: &mut FastRng) -> Duration {
+        let backoff = self.backoff(attempt);
+        if self.jitter {
+            backoff.mul_f64(rng.next_f64())
+        } else {
+            backoff
+        }
+    }
+
+    /// `true` if retry number `attempt` is still allowed
+    pub(crate) fn allows(&self, attempt: u32) -> bool {
+        !matches!(self.max_retries, Some(max) if attempt >= max)
+    }
+}
+
+/// Reconnects left under a [`ReconnectPolicy::retry_budget`]
+#[derive(Debug, Clone)]
+pub(crate) struct RetryBudget {
+    left: u32,
+    full: u32,
+    refill_after: Duration,
+}
+
+impl RetryBudget {
+    /// Reconnects left
+    pub(crate) fn left(&self) -> u32 {
+        self.left
+    }
+
+    /// Takes one reconnect after a connection that stayed subscribed for
+    /// `uptime`, refilling first if it was long enough. `false` once spent.
+    pub(crate) fn spend(&mut self, uptime: Option<Duration>) -> bool {
+        if matches!(uptime, Some(uptime) if uptime >= self.refill_after) {
+            self.left = self.full;
+        }
+        if self.left == 0 {
+            return false;
+        }
+        self.left -= 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_backoff_is_capped() {
+        let policy = ReconnectPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_secs(1))
+            .max_retries(5);
+
+        let delays: Vec<_> = (0..5).map(|attempt| policy.backoff(attempt)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1000].map(Duration::from_millis)
+        );
+
+        assert!(policy.allows(4));
+        assert!(!policy.allows(5));
+    }
+
+    #[test]
+    fn full_jitter_stays_within_backoff() {
+        let policy = ReconnectPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .jitter(true);
+        let mut rng = FastRng::seeded(7);
+
+        let delays: Vec<_> = (0..100).map(|_| policy.delay(3, &mut rng)).collect();
+        assert!(delays.iter().all(|d| *d < Duration::from_millis(800)));
+        assert!(delays.windows(2).any(|w| w[0] != w[1]));
+
+        let fixed = ReconnectPolicy::new().initial_backoff(Duration::from_millis(100));
+        assert_eq!(fixed.delay(3, &mut rng), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn retry_budget_refills_after_healthy_run() {
+        let hour = Duration::from_secs(3600);
+        let mut budget = ReconnectPolicy::new()
+            .retry_budget(2, hour)
+            .budget()
+            .unwrap();
+
+        // short runs keep spending the budget
+        assert!(budget.spend(None));
+        assert!(budget.spend(Some(Duration::from_secs(5))));
+        assert!(!budget.spend(Some(Duration::from_secs(5))));
+
+        // a long enough run starts over
+        assert!(budget.spend(Some(hour)));
+        assert_eq!(budget.left(), 1);
+
+        assert!(ReconnectPolicy::new().budget().is_none());
+    }
+}
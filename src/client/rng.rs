@@ -0,0 +1,47 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// Small non-cryptographic PRNG (SplitMix64) used for sampling and jitter
+#[derive(Debug, Clone)]
+pub(crate) struct FastRng {
+    state: u64,
+}
+
+impl FastRng {
+    /// Seeded from the per-process random keys of std's `RandomState`
+    pub(crate) fn from_entropy() -> Self {
+        Self::seeded(RandomState::new().build_hasher().finish())
+    }
+
+    pub(crate) fn seeded(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in `[0, 1)`
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uniform_enough_for_sampling() {
+        let mut rng = FastRng::seeded(42);
+        let sampled = (0..10_000).filter(|_| rng.next_f64() < 0.1).count();
+
+        assert!((800..1200).contains(&sampled), "sampled {sampled}");
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+#[cfg(feature = "prometheus")]
+use crate::client::metrics::Metrics;
+use crate::{client::ReconnectEvent, types::EdenPendingTx};
+
+/// Counters updated by the read loop
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    txs_received: AtomicU64,
+    unrecovered_senders: AtomicU64,
+    reconnects: AtomicU64,
+    parse_errors: AtomicU64,
+    channel_depth: AtomicU64,
+    filtered_out: AtomicU64,
+    sampled_out: AtomicU64,
+    oversized_frames: AtomicU64,
+    invalid_signatures: AtomicU64,
+    shed: AtomicU64,
+    /// Reconnects left under the retry budget plus one, `0` without a budget
+    retry_budget: AtomicU64,
+    /// Received txs by type, indexed like `tx_type_index`
+    tx_types: [AtomicU64; 5],
+    /// Most recent reconnects, oldest first
+    reconnect_history: Mutex<VecDeque<ReconnectEvent>>,
+    /// Collectors mirroring the counters, registered via `Client::register_metrics`
+    #[cfg(feature = "prometheus")]
+    metrics: Option<Metrics>,
+}
+
+impl Stats {
+    #[cfg(feature = "prometheus")]
+    pub(crate) fn with_metrics(metrics: Option<Metrics>) -> Self {
+        Self {
+            metrics,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn record_retry_budget(&self, left: u32) {
+        self.retry_budget
+            .store(u64::from(left) + 1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_tx(&self) {
+        self.txs_received.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(metrics) = &self.metrics {
+            metrics.txs_received.inc();
+        }
+    }
+
+    pub(crate) fn record_tx_type(&self, tx: &EdenPendingTx) {
+        self.tx_types[tx_type_index(tx)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_unrecovered_sender(&self) {
+        self.unrecovered_senders.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_filtered(&self) {
+        self.filtered_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_sampled_out(&self) {
+        self.sampled_out.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_oversized_frame(&self) {
+        self.oversized_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_invalid_signature(&self) {
+        self.invalid_signatures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_shed(&self) {
+        self.shed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts the reconnect and keeps `event` among the last `keep` ones
+    pub(crate) fn record_reconnect(&self, event: ReconnectEvent, keep: usize) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+
+        let mut history = self.reconnect_history.lock().unwrap();
+        history.push_back(event);
+        while history.len() > keep {
+            history.pop_front();
+        }
+        drop(history);
+
+        #[cfg(feature = "prometheus")]
+        if let Some(metrics) = &self.metrics {
+            metrics.reconnects.inc();
+        }
+    }
+
+    pub(crate) fn record_parse_error(&self) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(metrics) = &self.metrics {
+            metrics.parse_errors.inc();
+        }
+    }
+
+    /// Item handed to the channel, called before sending so the depth never underflows
+    pub(crate) fn record_queued(&self) {
+        self.channel_depth.fetch_add(1, Ordering::Relaxed);
+        #[cfg(feature = "prometheus")]
+        if let Some(metrics) = &self.metrics {
+            metrics.channel_depth.inc();
+        }
+    }
+
+    /// Item taken out of the channel by the consumer
+    pub(crate) fn record_dequeued(&self) {
+        let _ = self
+            .channel_depth
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |depth| {
+                Some(depth.saturating_sub(1))
+            });
+        #[cfg(feature = "prometheus")]
+        if let Some(metrics) = &self.metrics {
+            metrics.channel_depth.dec();
+        }
+    }
+
+    pub(crate) fn reconnect_history(&self) -> Vec<ReconnectEvent> {
+        self.reconnect_history
+            .lock()
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn snapshot(&self) -> StatsSnapshot {
+        StatsSnapshot {
+            txs_received: self.txs_received.load(Ordering::Relaxed),
+            unrecovered_senders: self.unrecovered_senders.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            parse_errors: self.parse_errors.load(Ordering::Relaxed),
+            channel_depth: self.channel_depth.load(Ordering::Relaxed),
+            filtered_out: self.filtered_out.load(Ordering::Relaxed),
+            sampled_out: self.sampled_out.load(Ordering::Relaxed),
+            oversized_frames: self.oversized_frames.load(Ordering::Relaxed),
+            invalid_signatures: self.invalid_signatures.load(Ordering::Relaxed),
+            shed: self.shed.load(Ordering::Relaxed),
+            retry_budget: self
+                .retry_budget
+                .load(Ordering::Relaxed)
+                .checked_sub(1)
+                .and_then(|left| u32::try_from(left).ok()),
+            tx_types: TxTypeCounts {
+                legacy: self.tx_types[0].load(Ordering::Relaxed),
+                eip2930: self.tx_types[1].load(Ordering::Relaxed),
+                eip1559: self.tx_types[2].load(Ordering::Relaxed),
+                eip4844: self.tx_types[3].load(Ordering::Relaxed),
+                other: self.tx_types[4].load(Ordering::Relaxed),
+            },
+        }
+    }
+}
+
+// slot of the tx's type in `Stats::tx_types`
+fn tx_type_index(tx: &EdenPendingTx) -> usize {
+    if tx.is_legacy() {
+        0
+    } else if tx.is_eip2930() {
+        1
+    } else if tx.is_eip1559() {
+        2
+    } else if tx.is_eip4844() {
+        3
+    } else {
+        4
+    }
+}
+
+/// Point-in-time copy of a subscription's counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    /// Notifications received from the server
+    pub txs_received: u64,
+    /// Txs whose missing sender could not be recovered
+    pub unrecovered_senders: u64,
+    /// Reconnection attempts after the connection dropped
+    pub reconnects: u64,
+    /// Messages that failed to parse
+    pub parse_errors: u64,
+    /// Txs delivered to the channel but not yet consumed
+    pub channel_depth: u64,
+    /// Txs dropped by subscription filters before reaching the channel
+    pub filtered_out: u64,
+    /// Txs dropped by sampling before reaching the channel
+    pub sampled_out: u64,
+    /// Frames over the message size limit, each failing its connection
+    pub oversized_frames: u64,
+    /// Txs dropped for a malformed signature before reaching the channel
+    pub invalid_signatures: u64,
+    /// Txs dropped by the inbound rate limit before reaching the channel
+    pub shed: u64,
+    /// Reconnects left under
+    /// [`ReconnectPolicy::retry_budget`](crate::client::ReconnectPolicy::retry_budget),
+    /// `None` without a budget
+    pub retry_budget: Option<u32>,
+    /// Received txs by type, counted before any filtering
+    pub tx_types: TxTypeCounts,
+}
+
+/// Received txs broken down by type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TxTypeCounts {
+    /// Legacy (type `0x0`) txs
+    pub legacy: u64,
+    /// EIP-2930 access list (type `0x1`) txs
+    pub eip2930: u64,
+    /// EIP-1559 dynamic fee (type `0x2`) txs
+    pub eip1559: u64,
+    /// EIP-4844 blob (type `0x3`) txs
+    pub eip4844: u64,
+    /// Txs of any other type
+    pub other: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+    use crate::{client::TerminationReason, test_utils::sample_tx};
+
+    #[test]
+    fn reconnect_history_is_bounded() {
+        let stats = Stats::default();
+        for attempt in 1..=5 {
+            let event = ReconnectEvent {
+                at: SystemTime::now(),
+                reason: TerminationReason::ConnectionLost,
+                backoff: Duration::ZERO,
+                attempt,
+            };
+            stats.record_reconnect(event, 3);
+        }
+
+        let attempts: Vec<_> = stats
+            .reconnect_history()
+            .iter()
+            .map(|e| e.attempt)
+            .collect();
+        assert_eq!(attempts, [3, 4, 5]);
+        assert_eq!(stats.snapshot().reconnects, 5);
+    }
+
+    #[test]
+    fn counts_txs_by_type() {
+        let stats = Stats::default();
+        for r#type in [0u64, 2, 2, 3, 0x7e] {
+            let mut tx = sample_tx();
+            tx.r#type = r#type.into();
+            stats.record_tx_type(&tx);
+        }
+
+        let expected = TxTypeCounts {
+            legacy: 1,
+            eip2930: 0,
+            eip1559: 2,
+            eip4844: 1,
+            other: 1,
+        };
+        assert_eq!(stats.snapshot().tx_types, expected);
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Poll},
+};
+
+use eyre::{eyre, Result};
+use futures_util::Stream;
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use url::Url;
+
+use crate::{
+    client::{stats::Stats, task::Command, ReconnectEvent, StatsSnapshot},
+    json_rpc::response::Response,
+    types::{EdenPendingTx, RawTx, Sequenced},
+};
+
+/// Why a subscription's background task stopped
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TerminationReason {
+    /// The server closed the connection with a close frame
+    ServerClosed,
+    /// The connection ended without a close frame
+    ConnectionLost,
+    /// The subscription was closed through [`Subscription::close`]
+    ClientClosed,
+    /// The [`Subscription`] handle was dropped, a normal shutdown
+    Dropped,
+    /// No data frame arrived within the configured
+    /// [`idle_timeout`](crate::client::SubscriptionOptions::idle_timeout)
+    Idle,
+    /// The task failed with an error
+    Error(String),
+}
+
+/// Lifecycle of a subscription's connection, see [`Subscription::state_changes`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectionState {
+    /// Opening the WebSocket connection
+    Connecting,
+    /// Connected, subscribe requests not yet sent
+    Connected,
+    /// Every feed is subscribed and txs are streaming
+    Subscribed,
+    /// The connection dropped, waiting for the backoff before reconnecting
+    Reconnecting,
+    /// The subscription terminated, see [`Subscription::termination_reason`]
+    Closed,
+}
+
+/// Server's answer to the subscribe request of the current connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Ack {
+    Pending,
+    Confirmed,
+    Rejected(String),
+}
+
+/// Result of the close handshake performed by [`Subscription::close`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloseOutcome {
+    /// The server replied with its own close frame before the timeout
+    pub acknowledged: bool,
+    /// Close code of the server's reply
+    pub code: Option<u16>,
+    /// Close reason of the server's reply
+    pub reason: Option<String>,
+}
+
+/// Handle to a live subscription, yielding txs as a [`Stream`]
+#[derive(Debug)]
+pub struct Subscription<T = EdenPendingTx> {
+    pub(crate) rx: UnboundedReceiverStream<Sequenced<EdenPendingTx>>,
+    pub(crate) termination: Arc<OnceLock<TerminationReason>>,
+    pub(crate) stats: Arc<Stats>,
+    pub(crate) commands: mpsc::UnboundedSender<Command>,
+    pub(crate) label: Option<Arc<str>>,
+    pub(crate) responses: Option<UnboundedReceiverStream<Response>>,
+    pub(crate) state: watch::Receiver<ConnectionState>,
+    pub(crate) ack: watch::Receiver<Ack>,
+    pub(crate) item: PhantomData<fn() -> T>,
+}
+
+impl Subscription {
+    /// Yield every tx together with its arrival sequence number
+    pub fn sequenced(self) -> Subscription<Sequenced<EdenPendingTx>> {
+        self.with_item()
+    }
+
+    /// Yield every tx together with its signed encoding, computed by the read
+    /// loop with [`SubscriptionOptions::raw_encoding`](crate::client::SubscriptionOptions::raw_encoding)
+    pub fn raw(self) -> Subscription<RawTx> {
+        self.with_item()
+    }
+
+    // same subscription yielding another item type
+    fn with_item<T>(self) -> Subscription<T> {
+        Subscription {
+            rx: self.rx,
+            termination: self.termination,
+            stats: self.stats,
+            commands: self.commands,
+            label: self.label,
+            responses: self.responses,
+            state: self.state,
+            ack: self.ack,
+            item: PhantomData,
+        }
+    }
+}
+
+impl<T> Subscription<T> {
+    /// Why the feed stopped, `None` while it is still running.
+    ///
+    /// The reason is recorded before the stream ends, so once `next()`
+    /// returns `None` this is always `Some`.
+    pub fn termination_reason(&self) -> Option<TerminationReason> {
+        self.termination.get().cloned()
+    }
+
+    /// The error that terminated the subscription, `None` while it is running
+    /// or if it ended without an error, e.g. after [`close`](Self::close).
+    pub fn close_reason(&self) -> Option<String> {
+        match self.termination.get()? {
+            TerminationReason::Error(e) => Some(e.clone()),
+            _ => None,
+        }
+    }
+
+    /// Close the connection with a close handshake.
+    ///
+    /// Sends a close frame and waits up to 5 seconds for the server's reply.
+    /// Errors if the subscription has already terminated. Txs buffered before
+    /// the close stay readable, through the stream or [`drain`](Self::drain).
+    pub async fn close(&self) -> Result<CloseOutcome> {
+        let (reply, outcome) = oneshot::channel();
+        self.commands
+            .send(Command::Close { reply })
+            .map_err(|_| eyre!("subscription has already terminated"))?;
+
+        outcome
+            .await
+            .map_err(|_| eyre!("subscription terminated before closing"))
+    }
+
+    /// Move the live subscription to `url` without ending the stream.
+    ///
+    /// Connects and resubscribes every feed on the new endpoint first, then
+    /// swaps it in and closes the old connection, still delivering txs that
+    /// arrive on it until the server acknowledges the close. Txs seen by both
+    /// endpoints during the handover may be delivered twice. If the new
+    /// endpoint fails the error is returned and the current connection is
+    /// kept. Later reconnects target `url`.
+    pub async fn switch_endpoint(&self, url: Url) -> Result<()> {
+        let req = url.into_client_request()?;
+        let (reply, outcome) = oneshot::channel();
+        self.commands
+            .send(Command::SwitchEndpoint { req, reply })
+            .map_err(|_| eyre!("subscription has already terminated"))?;
+
+        outcome
+            .await
+            .map_err(|_| eyre!("subscription terminated before switching"))?
+    }
+
+    /// Take the stream of JSON-RPC responses received by the subscription.
+    ///
+    /// Yields subscription acks and any other out-of-band response. `None`
+    /// unless [`forward_responses`](crate::client::SubscriptionOptions::forward_responses)
+    /// is enabled, or if the stream was already taken.
+    pub fn responses(&mut self) -> Option<UnboundedReceiverStream<Response>> {
+        self.responses.take()
+    }
+
+    /// Watch the connection lifecycle, updated by the read and reconnect loop.
+    ///
+    /// Ends at [`ConnectionState::Closed`] once the subscription terminates.
+    pub fn state_changes(&self) -> watch::Receiver<ConnectionState> {
+        self.state.clone()
+    }
+
+    /// Wait until the server acknowledged the subscribe request of the current
+    /// connection.
+    ///
+    /// Errors if the server answered with an error, or if the subscription
+    /// terminated before an answer arrived. After a reconnect it waits for
+    /// the new connection's acknowledgement.
+    pub async fn wait_until_subscribed(&self) -> Result<()> {
+        let mut ack = self.ack.clone();
+        let ack = ack
+            .wait_for(|ack| *ack != Ack::Pending)
+            .await
+            .map_err(|_| eyre!("subscription terminated before it was acknowledged"))?
+            .clone();
+
+        match ack {
+            Ack::Rejected(e) => Err(eyre!("subscription rejected: {e}")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Label of the endpoint, set through `ClientBuilder::label`
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Most recent reconnects, oldest first, bounded by
+    /// [`ReconnectPolicy::history_len`](crate::client::ReconnectPolicy::history_len)
+    pub fn reconnect_history(&self) -> Vec<ReconnectEvent> {
+        self.stats.reconnect_history()
+    }
+
+    /// Current values of the subscription's counters
+    pub fn stats(&self) -> StatsSnapshot {
+        self.stats.snapshot()
+    }
+}
+
+impl<T: From<Sequenced<EdenPendingTx>>> Subscription<T> {
+    /// Collect every tx already buffered in the channel without waiting.
+    ///
+    /// The channel stops accepting txs first, so the stream is terminated
+    /// afterwards and the next `next()` returns `None`. Call it after
+    /// [`close`](Self::close) to process in-flight data on shutdown, a still
+    /// running task stops at its next delivery instead.
+    pub fn drain(&mut self) -> Vec<T> {
+        self.rx.close();
+
+        let mut items = Vec::new();
+        while let Ok(item) = self.rx.as_mut().try_recv() {
+            self.stats.record_dequeued();
+            items.push(T::from(item));
+        }
+
+        items
+    }
+}
+
+impl<T: From<Sequenced<EdenPendingTx>>> Stream for Subscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let item = Pin::new(&mut self.rx).poll_next(cx);
+        if let Poll::Ready(Some(_)) = item {
+            self.stats.record_dequeued();
+        }
+
+        item.map(|item| item.map(T::from))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rx.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use futures_util::StreamExt;
+    use static_assertions::assert_impl_all;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    // handles are shared across tasks and composed with combinators
+    assert_impl_all!(Subscription: Send, Sync, Unpin);
+    assert_impl_all!(Subscription<Sequenced<EdenPendingTx>>: Send, Sync, Unpin);
+
+    fn subscription() -> (
+        mpsc::UnboundedSender<Sequenced<EdenPendingTx>>,
+        Subscription,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let sub = Subscription {
+            rx: UnboundedReceiverStream::new(rx),
+            termination: Arc::new(OnceLock::new()),
+            stats: Arc::new(Stats::default()),
+            commands: mpsc::unbounded_channel().0,
+            label: None,
+            responses: None,
+            state: watch::channel(ConnectionState::Connecting).1,
+            ack: watch::channel(Ack::Pending).1,
+            item: PhantomData,
+        };
+        (tx, sub)
+    }
+
+    #[tokio::test]
+    async fn yields_pending_txs() {
+        let (tx, mut sub) = subscription();
+        tx.send(Sequenced {
+            seq: 0,
+            received_at: SystemTime::now(),
+            snapshot: false,
+            label: None,
+            raw: None,
+            item: sample_tx(),
+        })
+        .unwrap();
+        drop(tx);
+
+        let item: Option<EdenPendingTx> = sub.next().await;
+        assert_eq!(item.map(|tx| tx.hash), Some(sample_tx().hash));
+        assert!(sub.next().await.is_none());
+        assert_eq!(sub.stats().channel_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn drain_collects_buffered_and_terminates() {
+        let (tx, mut sub) = subscription();
+        for seq in 0..3 {
+            tx.send(Sequenced {
+                seq,
+                received_at: SystemTime::now(),
+                snapshot: false,
+                label: None,
+                raw: None,
+                item: sample_tx(),
+            })
+            .unwrap();
+        }
+
+        assert_eq!(sub.drain().len(), 3);
+        assert!(tx.is_closed());
+        assert!(sub.next().await.is_none());
+        assert_eq!(sub.stats().channel_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn sequenced_items_keep_seq() {
+        let (tx, sub) = subscription();
+        let sub = sub.sequenced();
+
+        for seq in [0, 1, 3] {
+            tx.send(Sequenced {
+                seq,
+                received_at: SystemTime::now(),
+                snapshot: false,
+                label: None,
+                raw: None,
+                item: sample_tx(),
+            })
+            .unwrap();
+        }
+        drop(tx);
+
+        let seqs: Vec<_> = sub.map(|item| item.seq).collect().await;
+        assert_eq!(seqs, vec![0, 1, 3]);
+    }
+}
@@ -0,0 +1,665 @@
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use eyre::{eyre, Result};
+use futures_util::{SinkExt, StreamExt};
+use tokio::{
+    sync::{mpsc, oneshot, watch},
+    time::Instant,
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        error::CapacityError,
+        handshake::client::Request,
+        protocol::{frame::coding::CloseCode, CloseFrame, WebSocketConfig},
+        Error as WsError, Message,
+    },
+    Connector,
+};
+
+#[cfg(feature = "testing")]
+use crate::client::ChaosConfig;
+use crate::{
+    client::{
+        builder::{message_size_limit, OnMessage},
+        connect_stream,
+        options::SubscriptionOptions,
+        rate_limit::TokenBucket,
+        rng::FastRng,
+        stats::Stats,
+        subscription::Ack,
+        transport::Target,
+        with_subprotocols, CloseOutcome, ConnectionState, Reader, ReconnectEvent, ReconnectPolicy,
+        SubscriptionKind, TerminationReason, Writer,
+    },
+    json_rpc::{
+        id::Id,
+        notification::EdenItem,
+        response::{Response, ResponsePayload},
+    },
+    types::{EdenPendingTx, Sequenced},
+};
+
+/// How long `close()` waits for the server's close reply
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Requests sent from a [`Subscription`](crate::client::Subscription) to its task
+#[derive(Debug)]
+pub(crate) enum Command {
+    /// Perform the close handshake and stop the task
+    Close {
+        reply: oneshot::Sender<CloseOutcome>,
+    },
+    /// Move the subscription to another endpoint
+    SwitchEndpoint {
+        req: Request,
+        reply: oneshot::Sender<Result<()>>,
+    },
+}
+
+/// Background task driving a single subscription
+pub(crate) struct SubscriptionTask {
+    pub(crate) req: Request,
+    pub(crate) connector: Option<Connector>,
+    pub(crate) limiter: Option<TokenBucket>,
+    /// Inbound rate limit, txs over it are shed
+    pub(crate) shedder: Option<TokenBucket>,
+    pub(crate) reconnect: Option<ReconnectPolicy>,
+    /// Feeds replayed on every (re)connection
+    pub(crate) feeds: Vec<SubscriptionKind>,
+    pub(crate) options: SubscriptionOptions,
+    pub(crate) stats: Arc<Stats>,
+    pub(crate) rng: FastRng,
+    pub(crate) tx: mpsc::UnboundedSender<Sequenced<EdenPendingTx>>,
+    /// Side channel for responses, set when forwarding is enabled
+    pub(crate) responses: Option<mpsc::UnboundedSender<Response>>,
+    pub(crate) commands: mpsc::UnboundedReceiver<Command>,
+    /// Sequence number of the next delivered tx, kept across connections
+    pub(crate) seq: u64,
+    /// Endpoint label stamped on every delivered tx
+    pub(crate) label: Option<Arc<str>>,
+    /// Max time a single frame write may take
+    pub(crate) write_timeout: Duration,
+    pub(crate) on_message: Option<OnMessage>,
+    /// Interval between keepalive pings, `None` sends none
+    pub(crate) keepalive: Option<Duration>,
+    /// Max silence on the socket before reconnecting, `None` waits forever
+    pub(crate) read_timeout: Option<Duration>,
+    /// Address or socket to connect to instead of resolving the host of `req`
+    pub(crate) target: Option<Target>,
+    /// Faults injected into data frames
+    #[cfg(feature = "testing")]
+    pub(crate) chaos: Option<ChaosConfig>,
+    /// Log every frame at trace level, `Some(true)` including its payload
+    pub(crate) trace_frames: Option<bool>,
+    /// Subprotocols offered in every handshake, including switched endpoints
+    pub(crate) subprotocols: Vec<String>,
+    /// Number of the last auto-numbered request
+    pub(crate) next_request_id: u64,
+    /// Id of the last subscribe request, matched against acks
+    pub(crate) subscribe_id: Option<Id>,
+    /// Lifecycle updates observed through `Subscription::state_changes`
+    pub(crate) state: watch::Sender<ConnectionState>,
+    /// Answer to the subscribe request, awaited by `Subscription::wait_until_subscribed`
+    pub(crate) ack: watch::Sender<Ack>,
+    /// Messages in a row that failed to parse, kept across connections
+    pub(crate) parse_failures: u32,
+    /// WebSocket settings of the next connection
+    pub(crate) ws_config: Option<WebSocketConfig>,
+    /// Message size limit switched to after an oversized frame
+    pub(crate) fallback_max_message_size: Option<usize>,
+}
+
+impl SubscriptionTask {
+    // writes a frame, failing the connection if it doesn't complete in time
+    async fn send(&self, write: &mut Writer, message: Message) -> Result<()> {
+        self.trace_frame("out", &message);
+        tokio::time::timeout(self.write_timeout, write.send(message))
+            .await
+            .map_err(|_| eyre!("write timed out after {:?}", self.write_timeout))??;
+        Ok(())
+    }
+
+    // logs `frame` when frame tracing is enabled
+    fn trace_frame(&self, direction: &'static str, frame: &Message) {
+        let Some(with_payload) = self.trace_frames else {
+            return;
+        };
+
+        let kind = match frame {
+            Message::Text(_) => "text",
+            Message::Binary(_) => "binary",
+            Message::Ping(_) => "ping",
+            Message::Pong(_) => "pong",
+            Message::Close(_) => "close",
+            Message::Frame(_) => "raw",
+        };
+        if with_payload {
+            tracing::trace!(direction, kind, len = frame.len(), payload = ?frame, "Frame");
+        } else {
+            tracing::trace!(direction, kind, len = frame.len(), "Frame");
+        }
+    }
+
+    // sends pending tx subscription msg
+    async fn subscribe_internal(&mut self, stream: &mut Writer, params: &[&str]) -> Result<()> {
+        let id = self.options.request_id.clone().unwrap_or_else(|| {
+            self.next_request_id += 1;
+            Id::Number(self.next_request_id)
+        });
+        self.subscribe_id = Some(id.clone());
+        let params = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "subscribe",
+            "params": params,
+        });
+
+        let params_str = serde_json::to_string(&params)?;
+
+        if let Some(limiter) = &mut self.limiter {
+            limiter.acquire().await;
+        }
+
+        self.send(stream, Message::Text(params_str)).await
+    }
+
+    // applies per-subscription filters and processing, `None` if the tx is dropped
+    fn process(&mut self, mut tx: EdenPendingTx) -> Option<EdenPendingTx> {
+        if !self.options.accepts(&tx) {
+            self.stats.record_filtered();
+            return None;
+        }
+
+        // sample before the costly steps below
+        if let Some(rate) = self.options.sample_rate {
+            if self.rng.next_f64() >= rate {
+                self.stats.record_sampled_out();
+                return None;
+            }
+        }
+
+        if let Some(shedder) = &mut self.shedder {
+            if !shedder.try_acquire() {
+                self.stats.record_shed();
+                return None;
+            }
+        }
+
+        if self.options.validate_signatures && !tx.has_valid_signature() {
+            tracing::debug!(hash = ?tx.hash, "Dropping tx with a malformed signature");
+            self.stats.record_invalid_signature();
+            return None;
+        }
+
+        if self.options.fill_missing_from && tx.from.is_zero() {
+            match tx.recover_signer() {
+                Ok(from) => tx.from = from,
+                Err(e) => {
+                    tracing::debug!(hash = ?tx.hash, error = ?e, "Failed to recover sender");
+                    self.stats.record_unrecovered_sender();
+                }
+            }
+        }
+
+        Some(tx)
+    }
+
+    // tags the tx with the next sequence number and hands it to the consumer
+    // returns `Dropped` once the consumer is gone
+    fn deliver(&mut self, tx: EdenPendingTx) -> Option<TerminationReason> {
+        let mut raw = None;
+        if self.options.raw_encoding {
+            match tx.rlp() {
+                Ok(rlp) => raw = Some(rlp),
+                Err(e) => tracing::debug!(hash = ?tx.hash, error = ?e, "Failed to encode tx"),
+            }
+        }
+
+        self.stats.record_queued();
+        let sent = self.tx.send(Sequenced {
+            seq: self.seq,
+            received_at: SystemTime::now(),
+            snapshot: false,
+            label: self.label.clone(),
+            raw,
+            item: tx,
+        });
+        if sent.is_err() {
+            tracing::debug!("Subscription dropped, stopping");
+            return Some(TerminationReason::Dropped);
+        }
+        self.seq += 1;
+
+        None
+    }
+
+    // handles a single frame, returns the termination reason if the connection is done
+    async fn handle_message(
+        &mut self,
+        write: &mut Writer,
+        payload: Message,
+    ) -> Result<Option<TerminationReason>> {
+        match payload {
+            Message::Text(text) => {
+                // deserialize
+                let expected_method = self.options.expected_method.as_deref();
+                let parsed = EdenItem::from_str_expecting(&text, expected_method);
+                return self.handle_item(parsed);
+            }
+            Message::Binary(_) => {
+                // Eden only sends text frames
+            }
+            Message::Pong(_) => {
+                // answers our keepalive pings, replying would ping-pong forever
+                tracing::debug!("Received Pong");
+            }
+            Message::Ping(ping_data) => {
+                tracing::debug!("Received Ping");
+                self.send(write, Message::Pong(ping_data)).await?;
+            }
+            Message::Close(frame) => {
+                if frame.is_some() {
+                    tracing::error!(?frame, "Received close frame with data");
+                } else {
+                    tracing::error!("WS server has gone away");
+                }
+                return Ok(Some(TerminationReason::ServerClosed));
+            }
+            Message::Frame(_) => {
+                // tungstenite buffers continuation frames and yields fragmented
+                // messages once complete, raw frames never come out of a read
+                tracing::warn!("Ignoring raw frame");
+            }
+        }
+
+        Ok(None)
+    }
+
+    // handles a parsed data frame, returns the termination reason if the connection is done
+    fn handle_item(
+        &mut self,
+        parsed: serde_json::Result<EdenItem>,
+    ) -> Result<Option<TerminationReason>> {
+        let item = match parsed {
+            Ok(item) => {
+                self.parse_failures = 0;
+                item
+            }
+            Err(e) => {
+                self.stats.record_parse_error();
+                self.parse_failures += 1;
+                if let Some(limit) = self.options.max_consecutive_parse_errors {
+                    if self.parse_failures >= limit {
+                        return Ok(Some(TerminationReason::Error(format!(
+                            "{} consecutive messages failed to parse, last: {e}",
+                            self.parse_failures
+                        ))));
+                    }
+                }
+                return Err(e.into());
+            }
+        };
+
+        // match if it is a `Notification` or `Response`
+        match item {
+            EdenItem::Response(r) => {
+                if r.payload.is_error() {
+                    tracing::error!("Error in reponse: {:?}", r.payload);
+                }
+                if self.subscribe_id.as_ref() == Some(&r.id) {
+                    self.ack.send_replace(match &r.payload {
+                        ResponsePayload::Success(subscription) => {
+                            tracing::info!(
+                                id = %r.id,
+                                subscription = subscription.get(),
+                                feeds = ?self.feeds,
+                                "Subscription acknowledged"
+                            );
+                            Ack::Confirmed
+                        }
+                        ResponsePayload::Failure(e) => Ack::Rejected(e.to_string()),
+                    });
+                }
+                if let Some(responses) = &self.responses {
+                    // the consumer may not care about responses
+                    let _ = responses.send(r);
+                }
+            }
+            EdenItem::Notification(n) => {
+                self.stats.record_tx();
+                self.stats.record_tx_type(&n.result);
+                if let Some(tx) = self.process(n.result) {
+                    if let Some(reason) = self.deliver(tx) {
+                        return Ok(Some(reason));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    // applies the configured faults to a data frame, `false` if it is dropped
+    #[cfg(feature = "testing")]
+    async fn inject_chaos(&mut self) -> Result<bool> {
+        let Some(chaos) = self.chaos else {
+            return Ok(true);
+        };
+
+        if let Some((rate, delay)) = chaos.delay {
+            if self.rng.next_f64() < rate {
+                tokio::time::sleep(delay).await;
+            }
+        }
+        if self.rng.next_f64() < chaos.drop_rate {
+            tracing::debug!("Chaos: dropping frame");
+            return Ok(false);
+        }
+        if self.rng.next_f64() < chaos.reconnect_rate {
+            return Err(eyre!("chaos: forced reconnect"));
+        }
+
+        Ok(true)
+    }
+
+    // sends a close frame and waits for the server's reply
+    async fn close_handshake(&self, write: &mut Writer, read: &mut Reader) -> CloseOutcome {
+        let frame = CloseFrame {
+            code: CloseCode::Normal,
+            reason: "".into(),
+        };
+        if let Err(e) = self.send(write, Message::Close(Some(frame))).await {
+            tracing::debug!(error = ?e, "Failed to send close frame");
+            return CloseOutcome::default();
+        }
+
+        // frames still in flight before the reply are discarded
+        let reply = tokio::time::timeout(CLOSE_TIMEOUT, async {
+            while let Some(Ok(payload)) = read.next().await {
+                self.trace_frame("in", &payload);
+                if let Message::Close(frame) = payload {
+                    return Some(frame);
+                }
+            }
+            None
+        })
+        .await;
+
+        match reply {
+            Ok(Some(frame)) => CloseOutcome {
+                acknowledged: true,
+                code: frame.as_ref().map(|f| u16::from(f.code)),
+                reason: frame.map(|f| f.reason.into_owned()),
+            },
+            Ok(None) => CloseOutcome::default(),
+            Err(_) => {
+                tracing::debug!("Timed out waiting for close reply");
+                CloseOutcome::default()
+            }
+        }
+    }
+
+    // delivers frames still in flight on a replaced connection until the server acknowledges the close
+    async fn drain(&mut self, mut write: Writer, mut read: Reader) {
+        let frame = CloseFrame {
+            code: CloseCode::Normal,
+            reason: "".into(),
+        };
+        if let Err(e) = self.send(&mut write, Message::Close(Some(frame))).await {
+            tracing::debug!(error = ?e, "Failed to close replaced connection");
+            return;
+        }
+
+        let drained = tokio::time::timeout(CLOSE_TIMEOUT, async {
+            while let Some(Ok(payload)) = read.next().await {
+                self.trace_frame("in", &payload);
+                if let Message::Close(_) = payload {
+                    break;
+                }
+                if let Err(e) = self.handle_message(&mut write, payload).await {
+                    tracing::debug!(error = ?e, "Failed to handle frame while draining");
+                }
+            }
+        })
+        .await;
+        if drained.is_err() {
+            tracing::debug!("Timed out draining replaced connection");
+        }
+    }
+
+    /// connects and streams, reconnecting per the policy until the subscription ends
+    pub(crate) async fn run(&mut self) -> Result<TerminationReason> {
+        let mut attempt = 0;
+        let mut budget = self.reconnect.as_ref().and_then(ReconnectPolicy::budget);
+        if let Some(budget) = &budget {
+            self.stats.record_retry_budget(budget.left());
+        }
+
+        loop {
+            let mut subscribed = false;
+            let started = Instant::now();
+            let outcome = self.connection(&mut subscribed).await;
+
+            let reason = match outcome {
+                Ok(TerminationReason::ClientClosed) => return Ok(TerminationReason::ClientClosed),
+                Ok(TerminationReason::Idle) => return Ok(TerminationReason::Idle),
+                Ok(TerminationReason::Dropped) => return Ok(TerminationReason::Dropped),
+                // reported by the connection itself, reconnecting won't help
+                Ok(reason @ TerminationReason::Error(_)) => return Ok(reason),
+                Ok(reason) => reason,
+                Err(e) => TerminationReason::Error(e.to_string()),
+            };
+
+            // nobody is listening anymore
+            if self.tx.is_closed() {
+                return Ok(reason);
+            }
+
+            let Some(policy) = &self.reconnect else {
+                return Ok(reason);
+            };
+            if subscribed {
+                attempt = 0;
+            }
+            if !policy.allows(attempt) {
+                tracing::error!(?reason, attempt, "Giving up reconnecting");
+                return Ok(reason);
+            }
+            if let Some(budget) = &mut budget {
+                let uptime = subscribed.then(|| started.elapsed());
+                let spent = budget.spend(uptime);
+                self.stats.record_retry_budget(budget.left());
+                if !spent {
+                    tracing::error!(?reason, "Retry budget exhausted, giving up reconnecting");
+                    return Ok(reason);
+                }
+            }
+
+            let backoff = policy.delay(attempt, &mut self.rng);
+            attempt += 1;
+            tracing::warn!(?reason, ?backoff, attempt, "Reconnecting");
+            let event = ReconnectEvent {
+                at: SystemTime::now(),
+                reason,
+                backoff,
+                attempt,
+            };
+            let keep = policy.history_len;
+            self.state.send_replace(ConnectionState::Reconnecting);
+
+            tokio::select! {
+                _ = tokio::time::sleep(backoff) => {}
+                command = self.commands.recv() => match command {
+                    Some(Command::Close { reply }) => {
+                        // no connection to close
+                        let _ = reply.send(CloseOutcome::default());
+                        return Ok(TerminationReason::ClientClosed);
+                    }
+                    Some(Command::SwitchEndpoint { req, reply }) => {
+                        match with_subprotocols(req, &self.subprotocols) {
+                            Ok(req) => {
+                                // picked up by the next attempt
+                                self.req = req;
+                                self.target = None;
+                                let _ = reply.send(Ok(()));
+                            }
+                            Err(e) => {
+                                let _ = reply.send(Err(e));
+                            }
+                        }
+                    }
+                    None => {
+                        tracing::debug!("Subscription dropped, giving up reconnecting");
+                        return Ok(TerminationReason::Dropped);
+                    }
+                },
+            }
+
+            self.stats.record_reconnect(event, keep);
+        }
+    }
+
+    // connects to `req` and subscribes every feed
+    async fn open(&mut self, req: Request, target: Option<&Target>) -> Result<(Writer, Reader)> {
+        let (mut write, read) = self.connect(req, target).await?;
+        self.subscribe_feeds(&mut write).await?;
+
+        Ok((write, read))
+    }
+
+    // connects and subscribes on a switched endpoint, returning its request
+    async fn open_endpoint(&mut self, req: Request) -> Result<(Request, Writer, Reader)> {
+        let req = with_subprotocols(req, &self.subprotocols)?;
+        let (write, read) = self.open(req.clone(), None).await?;
+
+        Ok((req, write, read))
+    }
+
+    async fn connect(&self, req: Request, target: Option<&Target>) -> Result<(Writer, Reader)> {
+        let stream = connect_stream(req, self.ws_config, self.connector.clone(), target).await?;
+        Ok(stream.split())
+    }
+
+    async fn subscribe_feeds(&mut self, write: &mut Writer) -> Result<()> {
+        for kind in self.feeds.clone() {
+            self.subscribe_internal(write, &[kind.as_str()]).await?;
+        }
+        Ok(())
+    }
+
+    // connects, subscribes every feed and forwards txs until the connection ends
+    #[tracing::instrument(
+        name = "eden_connection",
+        skip_all,
+        fields(uri = %self.req.uri(), label = self.label.as_deref())
+    )]
+    async fn connection(&mut self, subscribed: &mut bool) -> Result<TerminationReason> {
+        self.state.send_replace(ConnectionState::Connecting);
+        self.ack.send_replace(Ack::Pending);
+        let (mut write, mut read) = self.connect(self.req.clone(), self.target.as_ref()).await?;
+        self.state.send_replace(ConnectionState::Connected);
+        self.subscribe_feeds(&mut write).await?;
+        self.state.send_replace(ConnectionState::Subscribed);
+        *subscribed = true;
+
+        let idle_timeout = self.options.idle_timeout;
+        let idle = tokio::time::sleep(idle_timeout.unwrap_or(Duration::MAX));
+        tokio::pin!(idle);
+
+        let read_timeout = self.read_timeout;
+        let silent = tokio::time::sleep(read_timeout.unwrap_or(Duration::MAX));
+        tokio::pin!(silent);
+
+        let keepalive = self.keepalive;
+        // the branch is disabled without keepalive, any finite period avoids overflowing
+        let period = keepalive.unwrap_or(Duration::from_secs(3600));
+        let mut heartbeat = tokio::time::interval_at(Instant::now() + period, period);
+
+        // react to frames, commands from the handle and timers, whichever comes first
+        loop {
+            tokio::select! {
+                item = read.next() => match item {
+                    Some(Ok(payload)) => {
+                        self.trace_frame("in", &payload);
+                        if let Some(OnMessage(hook)) = &self.on_message {
+                            hook(&payload);
+                        }
+                        if let Some(timeout) = read_timeout {
+                            silent.as_mut().reset(Instant::now() + timeout);
+                        }
+                        let data = matches!(payload, Message::Text(_) | Message::Binary(_));
+                        if let (true, Some(timeout)) = (data, idle_timeout) {
+                            idle.as_mut().reset(Instant::now() + timeout);
+                        }
+                        #[cfg(feature = "testing")]
+                        if data && !self.inject_chaos().await? {
+                            continue;
+                        }
+                        if let Some(reason) = self.handle_message(&mut write, payload).await? {
+                            if matches!(reason, TerminationReason::Error(_) | TerminationReason::Dropped) {
+                                self.close_handshake(&mut write, &mut read).await;
+                            }
+                            return Ok(reason);
+                        }
+                    }
+                    Some(Err(WsError::Capacity(CapacityError::MessageTooLong { size, max_size }))) => {
+                        tracing::error!(size, max_size, "Frame exceeds the message size limit");
+                        self.stats.record_oversized_frame();
+                        if let Some(limit) = self.fallback_max_message_size {
+                            self.ws_config = Some(message_size_limit(limit));
+                        }
+                        return Err(eyre!("frame of {size} bytes exceeds the {max_size} byte limit"));
+                    }
+                    Some(Err(e)) => {
+                        tracing::error!(error = ?e, "Error in transaction stream");
+                        return Err(e.into());
+                    }
+                    None => return Ok(TerminationReason::ConnectionLost),
+                },
+                command = self.commands.recv() => match command {
+                    Some(Command::Close { reply }) => {
+                        let outcome = self.close_handshake(&mut write, &mut read).await;
+                        let _ = reply.send(outcome);
+                        return Ok(TerminationReason::ClientClosed);
+                    }
+                    Some(Command::SwitchEndpoint { req, reply }) => match self.open_endpoint(req).await {
+                        Ok((req, new_write, new_read)) => {
+                            // reconnects target the new endpoint from now on
+                            self.req = req;
+                            self.target = None;
+                            let old_write = std::mem::replace(&mut write, new_write);
+                            let old_read = std::mem::replace(&mut read, new_read);
+                            let _ = reply.send(Ok(()));
+                            self.drain(old_write, old_read).await;
+                        }
+                        Err(e) => {
+                            tracing::warn!(error = ?e, "Failed to switch endpoint, keeping the current one");
+                            let _ = reply.send(Err(e));
+                        }
+                    },
+                    // the handle is gone, don't wait for a tx to fail delivery
+                    None => {
+                        tracing::debug!("Subscription dropped, stopping");
+                        self.close_handshake(&mut write, &mut read).await;
+                        return Ok(TerminationReason::Dropped);
+                    }
+                },
+                _ = heartbeat.tick(), if keepalive.is_some() => {
+                    self.send(&mut write, Message::Ping(Vec::new())).await?;
+                }
+                _ = &mut silent, if read_timeout.is_some() => {
+                    tracing::warn!(timeout = ?read_timeout, "Socket went silent, reconnecting");
+                    return Err(eyre!("no frame received within {:?}", read_timeout.unwrap_or_default()));
+                }
+                _ = &mut idle, if idle_timeout.is_some() => {
+                    tracing::warn!(timeout = ?idle_timeout, "No data frames received, closing idle subscription");
+                    self.close_handshake(&mut write, &mut read).await;
+                    return Ok(TerminationReason::Idle);
+                }
+            }
+        }
+    }
+}
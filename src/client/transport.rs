@@ -0,0 +1,107 @@
+#[cfg(all(unix, feature = "unix"))]
+use std::path::PathBuf;
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(all(unix, feature = "unix"))]
+use tokio::net::UnixStream;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_tungstenite::tungstenite::http::Uri;
+
+/// Where to connect instead of resolving the url's host
+#[derive(Debug, Clone)]
+pub(crate) enum Target {
+    /// TCP address, see `ClientBuilder::resolve_to`
+    Addr(SocketAddr),
+    /// Unix domain socket path, see `ClientBuilder::unix_socket`
+    #[cfg(all(unix, feature = "unix"))]
+    Unix(PathBuf),
+}
+
+/// Byte stream a WebSocket connection runs over, below TLS if any
+#[derive(Debug)]
+pub enum Transport {
+    /// TCP connection
+    Tcp(TcpStream),
+    /// Unix domain socket, only with the `unix` feature
+    #[cfg(all(unix, feature = "unix"))]
+    Unix(UnixStream),
+}
+
+impl Transport {
+    /// Connects to `target`, or to the host and port of `uri` if `None`
+    pub(crate) async fn connect(uri: &Uri, target: Option<&Target>) -> io::Result<Self> {
+        let stream = match target {
+            Some(Target::Addr(addr)) => TcpStream::connect(addr).await?,
+            #[cfg(all(unix, feature = "unix"))]
+            Some(Target::Unix(path)) => {
+                return Ok(Transport::Unix(UnixStream::connect(path).await?))
+            }
+            None => {
+                let host = uri.host().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "url has no host")
+                })?;
+                // ipv6 hosts keep their brackets in the uri
+                let host = host.trim_start_matches('[').trim_end_matches(']');
+                let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+                    Some("wss") => 443,
+                    _ => 80,
+                });
+                TcpStream::connect((host, port)).await?
+            }
+        };
+
+        Ok(Transport::Tcp(stream))
+    }
+}
+
+impl AsyncRead for Transport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Transport::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(all(unix, feature = "unix"))]
+            Transport::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Transport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Transport::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(all(unix, feature = "unix"))]
+            Transport::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Transport::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            Transport::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Transport::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(all(unix, feature = "unix"))]
+            Transport::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
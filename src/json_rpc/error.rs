@@ -1,7 +1,7 @@
 use core::marker::PhantomData;
 
 use serde::{
-    de::{MapAccess, Visitor},
+    de::{DeserializeOwned, MapAccess, Visitor},
     Deserialize, Serialize,
 };
 use serde_json::value::RawValue;
@@ -21,6 +21,17 @@ pub struct ErrorPayload<ErrData = Box<RawValue>> {
     pub data: Option<ErrData>,
 }
 
+impl<ErrData: Serialize> ErrorPayload<ErrData> {
+    /// Deserialize the error data into `T`.
+    ///
+    /// Returns `None` if the error carries no data.
+    pub fn data_as<T: DeserializeOwned>(&self) -> Option<Result<T, serde_json::Error>> {
+        self.data
+            .as_ref()
+            .map(|data| serde_json::to_value(data).and_then(serde_json::from_value))
+    }
+}
+
 impl<ErrData> std::fmt::Display for ErrorPayload<ErrData> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -135,3 +146,33 @@ impl<'de, ErrData: Deserialize<'de>> Deserialize<'de> for ErrorPayload<ErrData>
         deserializer.deserialize_any(ErrorPayloadVisitor(PhantomData))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct RateLimited {
+        retry_after: u64,
+    }
+
+    #[test]
+    fn typed_error_data() {
+        let error = r#"{"code":-32005,"message":"rate limited","data":{"retry_after":5}}"#;
+        let payload: ErrorPayload = serde_json::from_str(error).unwrap();
+
+        let data = payload.data_as::<RateLimited>().unwrap().unwrap();
+        assert_eq!(data.retry_after, 5);
+
+        // mismatched shape surfaces the serde error
+        assert!(payload.data_as::<Vec<u64>>().unwrap().is_err());
+    }
+
+    #[test]
+    fn missing_error_data() {
+        let error = r#"{"code":-32700,"message":"Parse error"}"#;
+        let payload: ErrorPayload = serde_json::from_str(error).unwrap();
+
+        assert!(payload.data_as::<RateLimited>().is_none());
+    }
+}
@@ -2,13 +2,29 @@ use std::fmt::Display;
 
 use serde::{de::Visitor, Deserialize, Serialize};
 
+/// Backing type of [`Id::String`].
+///
+/// `String` by default, or `compact_str::CompactString` with the `compact-id`
+/// feature, which stores ids of up to 24 bytes inline. Both deref to `str`.
+#[cfg(not(feature = "compact-id"))]
+pub type IdString = String;
+/// Backing type of [`Id::String`].
+///
+/// `String` by default, or `compact_str::CompactString` with the `compact-id`
+/// feature, which stores ids of up to 24 bytes inline. Both deref to `str`.
+#[cfg(feature = "compact-id")]
+pub type IdString = compact_str::CompactString;
+
 /// A JSON-RPC 2.0 ID object. This may be a number, a string, or null.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Ids are ordered numbers first, then strings, then null, so they can key
+/// ordered maps.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum Id {
     /// A number.
     Number(u64),
     /// A string.
-    String(String),
+    String(IdString),
     /// Null.
     None,
 }
@@ -51,7 +67,7 @@ impl<'de> Deserialize<'de> for Id {
             where
                 E: serde::de::Error,
             {
-                Ok(Id::String(v.to_owned()))
+                Ok(Id::String(v.into()))
             }
 
             fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
@@ -112,3 +128,92 @@ impl Id {
         }
     }
 }
+
+impl From<Id> for serde_json::Value {
+    // a no-op unless `IdString` is a `CompactString`
+    #[allow(clippy::useless_conversion)]
+    fn from(id: Id) -> Self {
+        match id {
+            Id::Number(n) => n.into(),
+            Id::String(s) => serde_json::Value::String(s.into()),
+            Id::None => serde_json::Value::Null,
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for Id {
+    type Error = eyre::Report;
+
+    /// Accepts numbers fitting into `u64`, strings and null
+    #[allow(clippy::useless_conversion)]
+    fn try_from(value: serde_json::Value) -> eyre::Result<Self> {
+        match value {
+            serde_json::Value::Number(n) => n
+                .as_u64()
+                .map(Id::Number)
+                .ok_or_else(|| eyre::eyre!("id `{n}` is not an unsigned 64-bit integer")),
+            serde_json::Value::String(s) => Ok(Id::String(s.into())),
+            serde_json::Value::Null => Ok(Id::None),
+            other => Err(eyre::eyre!("invalid id type: {other}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn value_conversions() {
+        for id in [Id::Number(u64::MAX), Id::String("a".into()), Id::None] {
+            let value = serde_json::Value::from(id.clone());
+            assert_eq!(Id::try_from(value).unwrap(), id);
+        }
+
+        assert_eq!(Id::try_from(json!(7)).unwrap(), Id::Number(7));
+        assert_eq!(Id::try_from(json!(u64::MAX)).unwrap(), Id::Number(u64::MAX));
+        // one past u64::MAX only fits as a float
+        assert!(Id::try_from(json!(18446744073709551616.0)).is_err());
+        assert!(Id::try_from(json!(-1)).is_err());
+        assert!(Id::try_from(json!(1.5)).is_err());
+        assert!(Id::try_from(json!([1])).is_err());
+        assert!(Id::try_from(json!({"id": 1})).is_err());
+        assert!(Id::try_from(json!(true)).is_err());
+    }
+
+    #[cfg(feature = "compact-id")]
+    #[test]
+    fn short_string_ids_are_inline() {
+        let id: Id = serde_json::from_str(r#""req-42""#).unwrap();
+        let Id::String(s) = &id else {
+            panic!("expected a string id");
+        };
+        assert!(!s.is_heap_allocated());
+        assert_eq!(id.as_string(), Some("req-42"));
+    }
+
+    #[test]
+    fn ordering_is_stable() {
+        let mut ids = vec![
+            Id::None,
+            Id::String("b".into()),
+            Id::Number(2),
+            Id::String("a".into()),
+            Id::Number(1),
+        ];
+        ids.sort();
+
+        assert_eq!(
+            ids,
+            vec![
+                Id::Number(1),
+                Id::Number(2),
+                Id::String("a".into()),
+                Id::String("b".into()),
+                Id::None,
+            ]
+        );
+    }
+}
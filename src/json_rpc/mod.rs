@@ -2,3 +2,5 @@ pub mod error;
 pub mod id;
 pub mod notification;
 pub mod response;
+
+pub use notification::{parse_message, parse_notification};
@@ -1,5 +1,5 @@
 use serde::{
-    de::{MapAccess, Visitor},
+    de::{DeserializeSeed, MapAccess, Visitor},
     Deserialize, Serialize,
 };
 
@@ -14,22 +14,103 @@ pub enum EdenItem {
     Notification(EdenNotification),
 }
 
+/// Notification params, sent either as a `{subscription, result}` object or a
+/// `[subscription, result]` array
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct EdenNotification {
-    /// Subscription number
+    /// Subscription number, sent either as an integer or a hex quantity
+    #[serde(deserialize_with = "deserialize_quantity")]
     pub subscription: u64,
     /// Streamed msg
     pub result: EdenPendingTx,
 }
 
+/// Accepts a `u64` sent as a plain integer or a `0x` prefixed hex quantity
+fn deserialize_quantity<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct QuantityVisitor;
+
+    impl<'de> Visitor<'de> for QuantityVisitor {
+        type Value = u64;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            formatter.write_str("an integer or a hex quantity string")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            let digits = v
+                .strip_prefix("0x")
+                .ok_or_else(|| E::custom(format!("hex quantity `{v}` is missing the 0x prefix")))?;
+            u64::from_str_radix(digits, 16)
+                .map_err(|e| E::custom(format!("invalid hex quantity `{v}`: {e}")))
+        }
+    }
+
+    deserializer.deserialize_any(QuantityVisitor)
+}
+
+impl EdenItem {
+    /// Parse a raw message, rejecting notifications whose `method` isn't
+    /// `expected_method` when set
+    pub fn from_str_expecting(
+        text: &str,
+        expected_method: Option<&str>,
+    ) -> serde_json::Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(text);
+        let item = ExpectMethod(expected_method).deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(item)
+    }
+}
+
+/// Parse a single raw Eden message, e.g. received through a transport other
+/// than the bundled WebSocket client
+pub fn parse_message(text: &str) -> eyre::Result<EdenItem> {
+    Ok(serde_json::from_str(text)?)
+}
+
+/// Parse a single raw Eden message into its pending tx, `None` if the message
+/// is a response
+pub fn parse_notification(text: &str) -> eyre::Result<Option<EdenPendingTx>> {
+    match parse_message(text)? {
+        EdenItem::Notification(n) => Ok(Some(n.result)),
+        EdenItem::Response(_) => Ok(None),
+    }
+}
+
+/// Deserializes an [`EdenItem`], validating the notification `method` when
+/// set. Plain `Deserialize` accepts any method, e.g. Eden's `"subscription"`
+/// as well as the Ethereum-style `"eth_subscription"`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpectMethod<'a>(pub Option<&'a str>);
+
 impl<'de> Deserialize<'de> for EdenItem {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        struct EdenItemVisitor;
+        ExpectMethod(None).deserialize(deserializer)
+    }
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for ExpectMethod<'a> {
+    type Value = EdenItem;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct EdenItemVisitor<'a> {
+            expected_method: Option<&'a str>,
+        }
 
-        impl<'de> Visitor<'de> for EdenItemVisitor {
+        impl<'de, 'a> Visitor<'de> for EdenItemVisitor<'a> {
             type Value = EdenItem;
 
             fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -44,6 +125,7 @@ impl<'de> Deserialize<'de> for EdenItem {
                 let mut result = None;
                 let mut params = None;
                 let mut error = None;
+                let mut method: Option<String> = None;
 
                 // Drain the map into the appropriate fields
                 while let Ok(Some(key)) = map.next_key() {
@@ -66,6 +148,13 @@ impl<'de> Deserialize<'de> for EdenItem {
                             }
                             params = Some(map.next_value()?);
                         }
+                        // only read when checked, discarded like unknown fields otherwise
+                        "method" if self.expected_method.is_some() => {
+                            if method.is_some() {
+                                return Err(serde::de::Error::duplicate_field("method"));
+                            }
+                            method = Some(map.next_value()?);
+                        }
                         "error" => {
                             if error.is_some() {
                                 return Err(serde::de::Error::duplicate_field("error"));
@@ -81,14 +170,21 @@ impl<'de> Deserialize<'de> for EdenItem {
 
                 // If it has an ID, it is a response
                 if let Some(id) = id {
-                    let payload = error
-                        .map(ResponsePayload::Failure)
-                        .or_else(|| result.map(ResponsePayload::Success))
-                        .ok_or_else(|| {
-                            serde::de::Error::custom(
+                    // a response carries exactly one of `result` and `error`
+                    let payload = match (result, error) {
+                        (Some(result), None) => ResponsePayload::Success(result),
+                        (None, Some(error)) => ResponsePayload::Failure(error),
+                        (Some(_), Some(_)) => {
+                            return Err(serde::de::Error::custom(
+                                "response contains both `result` and `error` fields",
+                            ))
+                        }
+                        (None, None) => {
+                            return Err(serde::de::Error::custom(
                                 "missing `result` or `error` field in response",
-                            )
-                        })?;
+                            ))
+                        }
+                    };
 
                     Ok(EdenItem::Response(Response { id, payload }))
                 } else {
@@ -97,6 +193,13 @@ impl<'de> Deserialize<'de> for EdenItem {
                             "unexpected `error` field in subscription notification",
                         ));
                     }
+                    if let Some(expected) = self.expected_method {
+                        if method.as_deref() != Some(expected) {
+                            return Err(serde::de::Error::custom(format!(
+                                "unexpected notification method {method:?}, expected `{expected}`"
+                            )));
+                        }
+                    }
                     params
                         .map(EdenItem::Notification)
                         .ok_or_else(|| serde::de::Error::missing_field("params"))
@@ -104,7 +207,9 @@ impl<'de> Deserialize<'de> for EdenItem {
             }
         }
 
-        deserializer.deserialize_any(EdenItemVisitor)
+        deserializer.deserialize_any(EdenItemVisitor {
+            expected_method: self.0,
+        })
     }
 }
 
@@ -173,4 +278,172 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn deser_result_and_error_test() {
+        let both = r#"{"jsonrpc":"2.0","result":1,"error":{"code":-32700,"message":"Parse error"},"id":1}"#;
+
+        let err = serde_json::from_str::<EdenItem>(both).unwrap_err();
+        assert!(err.to_string().contains("both `result` and `error`"));
+    }
+
+    #[test]
+    fn parse_single_messages_test() -> Result<()> {
+        let notification = crate::test_utils::notification(crate::test_utils::TX);
+        let response = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+
+        assert!(matches!(
+            parse_message(&notification)?,
+            EdenItem::Notification(_)
+        ));
+        assert!(matches!(parse_message(response)?, EdenItem::Response(_)));
+        assert!(parse_message("not json").is_err());
+
+        let tx = parse_notification(&notification)?.expect("notification carries a tx");
+        assert_eq!(tx.hash, crate::test_utils::sample_tx().hash);
+        assert!(parse_notification(response)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn deser_array_params_test() -> Result<()> {
+        let tx = crate::test_utils::TX;
+        let array =
+            format!(r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":["0x1",{tx}]}}"#);
+        let object = crate::test_utils::notification(tx);
+
+        let (EdenItem::Notification(from_array), EdenItem::Notification(from_object)) =
+            (parse_message(&array)?, parse_message(&object)?)
+        else {
+            panic!("expected notifications");
+        };
+        assert_eq!(from_array.subscription, from_object.subscription);
+        assert_eq!(from_array.result.hash, from_object.result.hash);
+
+        let short = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":["0x1"]}"#;
+        assert!(parse_message(short).is_err());
+        let long = format!(
+            r#"{{"jsonrpc":"2.0","method":"eth_subscription","params":["0x1",{tx},null]}}"#
+        );
+        assert!(parse_message(&long).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn deser_expected_method_test() -> Result<()> {
+        let notification = crate::test_utils::notification(crate::test_utils::TX);
+
+        assert!(EdenItem::from_str_expecting(&notification, None).is_ok());
+        assert!(EdenItem::from_str_expecting(&notification, Some("subscription")).is_ok());
+
+        let err =
+            EdenItem::from_str_expecting(&notification, Some("eth_subscription")).unwrap_err();
+        assert!(err.to_string().contains("unexpected notification method"));
+
+        // responses carry no method
+        let response = r#"{"jsonrpc":"2.0","result":1,"id":1}"#;
+        assert!(EdenItem::from_str_expecting(response, Some("eth_subscription")).is_ok());
+
+        // a non-string method is only an error when checked
+        let numeric = notification.replace(r#""method":"subscription""#, r#""method":1"#);
+        assert!(EdenItem::from_str_expecting(&numeric, None).is_ok());
+        assert!(EdenItem::from_str_expecting(&numeric, Some("subscription")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn deser_subscription_quantity_test() -> Result<()> {
+        let parse = |id: &str| {
+            let raw = format!(
+                r#"{{"subscription":{id},"result":{}}}"#,
+                crate::test_utils::TX
+            );
+            serde_json::from_str::<EdenNotification>(&raw).map(|n| n.subscription)
+        };
+
+        assert_eq!(parse("4815270595554998")?, 4815270595554998);
+        assert_eq!(parse(r#""0x01""#)?, 1);
+        assert_eq!(parse(r#""0x111b768d586eb6""#)?, 4815270595554998);
+        assert_eq!(parse("18446744073709551615")?, u64::MAX);
+        assert_eq!(parse(r#""0xffffffffffffffff""#)?, u64::MAX);
+        assert!(parse(r#""0x10000000000000000""#).is_err());
+        assert!(parse(r#""12""#).is_err());
+
+        Ok(())
+    }
+
+    mod fuzz {
+        use proptest::prelude::*;
+        use serde_json::{json, Value};
+
+        use super::*;
+        use crate::test_utils::TX;
+
+        // arbitrary JSON biased towards the keys the visitor branches on
+        fn json_value() -> impl Strategy<Value = Value> {
+            let leaf = prop_oneof![
+                Just(Value::Null),
+                any::<bool>().prop_map(Value::from),
+                any::<i64>().prop_map(Value::from),
+                any::<u64>().prop_map(Value::from),
+                any::<f64>().prop_map(Value::from),
+                ".{0,8}".prop_map(Value::from),
+            ];
+            leaf.prop_recursive(4, 64, 6, |inner| {
+                prop_oneof![
+                    prop::collection::vec(inner.clone(), 0..4).prop_map(Value::Array),
+                    prop::collection::btree_map(
+                        "id|result|error|params|jsonrpc|method|subscription|[a-z]{1,6}",
+                        inner,
+                        0..6,
+                    )
+                    .prop_map(|map| Value::Object(map.into_iter().collect())),
+                ]
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn never_panics_on_random_text(text in ".{0,256}") {
+                let _ = serde_json::from_str::<EdenItem>(&text);
+            }
+
+            #[test]
+            fn never_panics_on_random_json(value in json_value()) {
+                let _ = serde_json::from_str::<EdenItem>(&value.to_string());
+            }
+
+            #[test]
+            fn classifies_responses(id in any::<u64>(), result in json_value(), failed in any::<bool>()) {
+                let response = if failed {
+                    json!({"jsonrpc": "2.0", "id": id, "error": {"code": -32000, "message": "err", "data": result}})
+                } else {
+                    json!({"jsonrpc": "2.0", "id": id, "result": result})
+                };
+
+                match serde_json::from_str::<EdenItem>(&response.to_string()) {
+                    Ok(EdenItem::Response(r)) => {
+                        prop_assert_eq!(r.id, Id::Number(id));
+                        prop_assert_eq!(r.payload.is_error(), failed);
+                    }
+                    other => prop_assert!(false, "misclassified response: {:?}", other),
+                }
+            }
+
+            #[test]
+            fn classifies_notifications(subscription in any::<u64>(), extra in json_value()) {
+                let notification = format!(
+                    r#"{{"jsonrpc":"2.0","method":"subscription","extra":{extra},"params":{{"subscription":{subscription},"result":{TX}}}}}"#
+                );
+
+                match serde_json::from_str::<EdenItem>(&notification) {
+                    Ok(EdenItem::Notification(n)) => prop_assert_eq!(n.subscription, subscription),
+                    other => prop_assert!(false, "misclassified notification: {:?}", other),
+                }
+            }
+        }
+    }
 }
@@ -1,5 +1,35 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
+//! Client for Eden Mempool Streaming services.
+//!
+//! ## TLS backends
+//!
+//! Exactly one TLS backend has to be enabled:
+//!
+//! | feature      | default | backend                        | custom config                  |
+//! |--------------|---------|--------------------------------|--------------------------------|
+//! | `rustls-tls` | yes     | rustls with webpki roots       | `ClientBuilder::tls_config`    |
+//! | `native-tls` | no      | platform library (OpenSSL etc) | -                              |
+//!
+//! Use `default-features = false, features = ["native-tls"]` to switch to
+//! native-tls.
+//!
+//! ## Debugging
+//!
+//! With the `console` feature and `RUSTFLAGS="--cfg tokio_unstable"` every
+//! subscription runs as a task named `eden-subscription`, visible in
+//! tokio-console. Connections are traced in an `eden_connection` span.
 
+#[cfg(all(feature = "rustls-tls", feature = "native-tls"))]
+compile_error!("features `rustls-tls` and `native-tls` are mutually exclusive");
+#[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+compile_error!("enable one of the `rustls-tls` or `native-tls` features");
+
+pub mod adapters;
 pub mod client;
 pub mod json_rpc;
+pub mod sink;
+pub mod source;
 pub mod types;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
@@ -0,0 +1,89 @@
+use std::{future::Future, time::Duration};
+
+use eyre::Result;
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    time::Instant,
+};
+
+use crate::types::EdenPendingTx;
+
+/// Push-model consumer of a subscription, see
+/// [`Client::run_with_sink`](crate::client::Client::run_with_sink)
+pub trait TxSink {
+    /// Handle a single tx, the next one is delivered once this completes
+    fn on_tx(&self, tx: EdenPendingTx) -> impl Future<Output = ()> + Send;
+}
+
+/// Max number of lines written between flushes
+const FLUSH_LINES: usize = 128;
+/// Max time between flushes while items keep arriving
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Writes every item of `stream` as a single JSON line into `writer`.
+///
+/// The writer is flushed every 128 lines or once a second, whichever comes
+/// first, and once more when the stream ends. Serialization and
+/// write errors are returned instead of being skipped. Returns the number of
+/// lines written.
+pub async fn write_jsonl<S, T, W>(mut stream: S, mut writer: W) -> Result<u64>
+where
+    S: Stream<Item = T> + Unpin,
+    T: Serialize,
+    W: AsyncWrite + Unpin,
+{
+    let mut written = 0;
+    let mut pending = 0;
+    let mut last_flush = Instant::now();
+    let mut line = Vec::new();
+
+    while let Some(item) = stream.next().await {
+        line.clear();
+        serde_json::to_writer(&mut line, &item)?;
+        line.push(b'\n');
+        writer.write_all(&line).await?;
+
+        written += 1;
+        pending += 1;
+
+        if pending >= FLUSH_LINES || last_flush.elapsed() >= FLUSH_INTERVAL {
+            writer.flush().await?;
+            pending = 0;
+            last_flush = Instant::now();
+        }
+    }
+
+    writer.flush().await?;
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    #[tokio::test]
+    async fn writes_one_line_per_tx() -> Result<()> {
+        let txs = vec![sample_tx(), sample_tx()];
+        let mut out = Vec::new();
+
+        let written = write_jsonl(stream::iter(txs), &mut out).await?;
+        assert_eq!(written, 2);
+
+        let text = String::from_utf8(out)?;
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        for line in lines {
+            let tx: EdenPendingTx = serde_json::from_str(line)?;
+            assert_eq!(tx.hash, sample_tx().hash);
+        }
+
+        Ok(())
+    }
+}
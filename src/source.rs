@@ -0,0 +1,95 @@
+use eyre::Result;
+use futures_util::{stream, Stream};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::{json_rpc::parse_notification, types::EdenPendingTx};
+
+/// Reads raw Eden messages, one JSON message per line, e.g. recorded frames
+/// piped through stdin.
+///
+/// Lines are parsed like frames of a live subscription: notifications yield
+/// their tx, while responses and blank lines are skipped. Unparsable lines are
+/// yielded as `Err` and reading continues with the next line. The stream ends
+/// with the input, or after yielding a read error as `Err`.
+pub fn read_jsonl<R>(reader: R) -> impl Stream<Item = Result<EdenPendingTx>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    // `None` once reading failed, the reader may keep failing
+    stream::unfold(Some(reader.lines()), |lines| async move {
+        let mut lines = lines?;
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => return None,
+                Err(e) => return Some((Err(e.into()), None)),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_notification(&line) {
+                Ok(Some(tx)) => return Some((Ok(tx), Some(lines))),
+                Ok(None) => continue,
+                Err(e) => return Some((Err(e), Some(lines))),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_util::StreamExt;
+    use tokio::io::{AsyncRead, AsyncReadExt, BufReader, ReadBuf};
+
+    use super::*;
+    use crate::test_utils::{notification, TX};
+
+    // fails every read, like a device returning EIO
+    struct Broken;
+
+    impl AsyncRead for Broken {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            Poll::Ready(Err(io::Error::other("broken")))
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_piped_notifications() {
+        let input = format!(
+            "{}\n\n{}\nnot json\n{}\n",
+            r#"{"jsonrpc":"2.0","result":1,"id":1}"#,
+            notification(TX),
+            notification(TX),
+        );
+
+        let items: Vec<_> = read_jsonl(input.as_bytes()).collect().await;
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().unwrap().nonce, 2.into());
+        assert!(items[1].is_err());
+        assert!(items[2].is_ok());
+    }
+
+    #[tokio::test]
+    async fn ends_after_read_error() {
+        let input = format!("{}\n", notification(TX));
+        let reader = BufReader::new(input.as_bytes().chain(Broken));
+
+        let items: Vec<_> = read_jsonl(reader).collect().await;
+
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        assert!(items[1].is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use alloy_consensus::{SignableTransaction, TxEip1559, TxEip2930, TxEnvelope, TxLegacy};
+use alloy_eips::eip2930::{AccessList as AlloyAccessList, AccessListItem};
+use alloy_primitives::{
+    Address as AlloyAddress, Bytes as AlloyBytes, Signature, TxKind, B256, U64 as AlloyU64,
+};
+use ethers_core::types::{
+    transaction::eip2930::{AccessList, AccessListItem as EthersAccessListItem},
+    Address, Bytes, H256, U256, U64,
+};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use super::EdenPendingTx;
+
+// ethers and alloy both store `U256` as little-endian u64 limbs
+fn to_alloy_u256(value: U256) -> alloy_primitives::U256 {
+    alloy_primitives::U256::from_limbs(value.0)
+}
+
+fn from_alloy_u256(value: alloy_primitives::U256) -> U256 {
+    U256(value.into_limbs())
+}
+
+fn to_alloy_access_list(list: &AccessList) -> AlloyAccessList {
+    AlloyAccessList(
+        list.0
+            .iter()
+            .map(|item| AccessListItem {
+                address: AlloyAddress::from(item.address.0),
+                storage_keys: item.storage_keys.iter().map(|k| B256::from(k.0)).collect(),
+            })
+            .collect(),
+    )
+}
+
+fn from_alloy_access_list(list: AlloyAccessList) -> AccessList {
+    AccessList(
+        list.0
+            .into_iter()
+            .map(|item| EthersAccessListItem {
+                address: Address::from(item.address.into_array()),
+                storage_keys: item.storage_keys.into_iter().map(|k| H256(k.0)).collect(),
+            })
+            .collect(),
+    )
+}
+
+// same encodings as `EdenPendingTx::chain_id`
+fn deserialize_chain_id<'de, D>(deserializer: D) -> Result<Option<alloy_primitives::U256>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(super::deserialize_chain_id(deserializer)?.map(to_alloy_u256))
+}
+
+fn to_u128(value: U256, field: &str) -> Result<u128> {
+    u128::try_from(value).map_err(|_| eyre!("`{field}` does not fit into u128"))
+}
+
+impl EdenPendingTx {
+    /// build the signed `alloy_consensus::TxEnvelope` matching the tx type.
+    ///
+    /// Supports legacy, EIP-2930 and EIP-1559 txs. EIP-4844 txs error since
+    /// `EdenPendingTx` carries no `maxFeePerBlobGas` or `blobVersionedHashes`
+    /// to sign over.
+    pub fn to_tx_envelope(&self) -> Result<TxEnvelope> {
+        let to = match self.to {
+            Some(to) => TxKind::Call(AlloyAddress::from(to.0)),
+            None => TxKind::Create,
+        };
+        let chain_id = self
+            .chain_id
+            .map(|id| u64::try_from(id).map_err(|_| eyre!("`chainId` does not fit into u64")))
+            .transpose()?;
+        let nonce =
+            u64::try_from(self.nonce).map_err(|_| eyre!("`nonce` does not fit into u64"))?;
+        let gas_limit =
+            u64::try_from(self.gas_limit).map_err(|_| eyre!("`gasLimit` does not fit into u64"))?;
+        let value = to_alloy_u256(self.value);
+        let input = AlloyBytes::copy_from_slice(&self.data);
+        let access_list = self
+            .access_list
+            .as_ref()
+            .map(to_alloy_access_list)
+            .unwrap_or_default();
+
+        // legacy txs carry EIP-155 `v`, typed txs carry the y-parity
+        let signature = Signature::from_rs_and_parity(
+            to_alloy_u256(self.r),
+            to_alloy_u256(self.s),
+            self.v.as_u64(),
+        )
+        .map_err(|e| eyre!("invalid signature: {e}"))?;
+
+        let envelope = match self.r#type.as_u64() {
+            0 => {
+                let gas_price = self
+                    .gas_price
+                    .ok_or_else(|| eyre!("legacy tx is missing `gasPrice`"))?;
+                TxLegacy {
+                    chain_id,
+                    nonce,
+                    gas_price: to_u128(gas_price, "gasPrice")?,
+                    gas_limit,
+                    to,
+                    value,
+                    input,
+                }
+                .into_signed(signature)
+                .into()
+            }
+            1 => {
+                let gas_price = self
+                    .gas_price
+                    .ok_or_else(|| eyre!("eip-2930 tx is missing `gasPrice`"))?;
+                TxEip2930 {
+                    chain_id: chain_id.ok_or_else(|| eyre!("eip-2930 tx is missing `chainId`"))?,
+                    nonce,
+                    gas_price: to_u128(gas_price, "gasPrice")?,
+                    gas_limit,
+                    to,
+                    value,
+                    access_list,
+                    input,
+                }
+                .into_signed(signature)
+                .into()
+            }
+            2 => {
+                let max_fee_per_gas = self
+                    .max_fee_per_gas
+                    .ok_or_else(|| eyre!("eip-1559 tx is missing `maxFeePerGas`"))?;
+                let max_priority_fee_per_gas = self
+                    .max_priority_fee_per_gas
+                    .ok_or_else(|| eyre!("eip-1559 tx is missing `maxPriorityFeePerGas`"))?;
+                TxEip1559 {
+                    chain_id: chain_id.ok_or_else(|| eyre!("eip-1559 tx is missing `chainId`"))?,
+                    nonce,
+                    gas_limit,
+                    max_fee_per_gas: to_u128(max_fee_per_gas, "maxFeePerGas")?,
+                    max_priority_fee_per_gas: to_u128(
+                        max_priority_fee_per_gas,
+                        "maxPriorityFeePerGas",
+                    )?,
+                    to,
+                    value,
+                    access_list,
+                    input,
+                }
+                .into_signed(signature)
+                .into()
+            }
+            3 => {
+                return Err(eyre!(
+                    "eip-4844 tx cannot be built: blob fields are not streamed"
+                ))
+            }
+            ty => return Err(eyre!("unsupported tx type {ty}")),
+        };
+
+        Ok(envelope)
+    }
+}
+
+/// [`EdenPendingTx`] with `alloy_primitives` field types, read from the same
+/// wire format
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct EdenPendingTxAlloy {
+    #[serde(default)]
+    pub r#type: AlloyU64,
+    pub hash: B256,
+    #[serde(default)]
+    pub from: AlloyAddress,
+    pub nonce: alloy_primitives::U256,
+    pub gas_limit: alloy_primitives::U256,
+    pub to: Option<AlloyAddress>,
+    /// Calldata, also accepted under the standard JSON-RPC name `input`
+    #[serde(alias = "input")]
+    pub data: AlloyBytes,
+    pub v: AlloyU64,
+    pub r: alloy_primitives::U256,
+    pub s: alloy_primitives::U256,
+    pub value: alloy_primitives::U256,
+    #[serde(default, deserialize_with = "deserialize_chain_id")]
+    pub chain_id: Option<alloy_primitives::U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AlloyAccessList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<alloy_primitives::U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<alloy_primitives::U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<alloy_primitives::U256>,
+}
+
+impl From<EdenPendingTx> for EdenPendingTxAlloy {
+    fn from(tx: EdenPendingTx) -> Self {
+        Self {
+            r#type: AlloyU64::from(tx.r#type.as_u64()),
+            hash: B256::from(tx.hash.0),
+            from: AlloyAddress::from(tx.from.0),
+            nonce: to_alloy_u256(tx.nonce),
+            gas_limit: to_alloy_u256(tx.gas_limit),
+            to: tx.to.map(|to| AlloyAddress::from(to.0)),
+            data: AlloyBytes::copy_from_slice(&tx.data),
+            v: AlloyU64::from(tx.v.as_u64()),
+            r: to_alloy_u256(tx.r),
+            s: to_alloy_u256(tx.s),
+            value: to_alloy_u256(tx.value),
+            chain_id: tx.chain_id.map(to_alloy_u256),
+            access_list: tx.access_list.as_ref().map(to_alloy_access_list),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(to_alloy_u256),
+            max_fee_per_gas: tx.max_fee_per_gas.map(to_alloy_u256),
+            gas_price: tx.gas_price.map(to_alloy_u256),
+        }
+    }
+}
+
+impl From<EdenPendingTxAlloy> for EdenPendingTx {
+    fn from(tx: EdenPendingTxAlloy) -> Self {
+        Self {
+            r#type: U64::from(tx.r#type.to::<u64>()),
+            hash: H256(tx.hash.0),
+            from: Address::from(tx.from.into_array()),
+            nonce: from_alloy_u256(tx.nonce),
+            gas_limit: from_alloy_u256(tx.gas_limit),
+            to: tx.to.map(|to| Address::from(to.into_array())),
+            data: Bytes::from(tx.data.to_vec()),
+            v: U64::from(tx.v.to::<u64>()),
+            r: from_alloy_u256(tx.r),
+            s: from_alloy_u256(tx.s),
+            value: from_alloy_u256(tx.value),
+            chain_id: tx.chain_id.map(from_alloy_u256),
+            access_list: tx.access_list.map(from_alloy_access_list),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(from_alloy_u256),
+            max_fee_per_gas: tx.max_fee_per_gas.map(from_alloy_u256),
+            gas_price: tx.gas_price.map(from_alloy_u256),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_eips::eip2718::{Decodable2718, Encodable2718};
+    use alloy_primitives::keccak256;
+
+    use super::*;
+    use crate::test_utils::{sample_tx, TX};
+
+    #[test]
+    fn alloy_mirror_matches_wire_format() -> Result<()> {
+        let alloy_tx: EdenPendingTxAlloy = serde_json::from_str(TX)?;
+        assert_eq!(alloy_tx, EdenPendingTxAlloy::from(sample_tx()));
+        assert_eq!(alloy_tx.nonce, alloy_primitives::U256::from(2));
+
+        let back = EdenPendingTx::from(alloy_tx.clone());
+        assert_eq!(
+            serde_json::to_value(&back)?,
+            serde_json::to_value(sample_tx())?
+        );
+        assert_eq!(
+            serde_json::to_value(&alloy_tx)?,
+            serde_json::to_value(sample_tx())?
+        );
+
+        // calldata under `input` and a decimal chain id
+        let payload = TX
+            .replace(r#""data":"#, r#""input":"#)
+            .replace(r#""chainId":"0x1""#, r#""chainId":1"#);
+        let alloy_tx: EdenPendingTxAlloy = serde_json::from_str(&payload)?;
+        assert_eq!(alloy_tx, EdenPendingTxAlloy::from(sample_tx()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tx_envelope_rlp_roundtrip() -> Result<()> {
+        let tx = sample_tx();
+        let envelope = tx.to_tx_envelope()?;
+
+        let mut encoded = Vec::new();
+        envelope.encode_2718(&mut encoded);
+        let decoded = TxEnvelope::decode_2718(&mut encoded.as_slice())?;
+
+        assert_eq!(decoded, envelope);
+        assert_eq!(keccak256(&encoded), B256::from(tx.hash.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn tx_envelope_rejects_blob_txs() {
+        let mut tx = sample_tx();
+        tx.r#type = U64::from(3);
+
+        let err = tx.to_tx_envelope().unwrap_err();
+        assert!(err.to_string().contains("eip-4844"));
+    }
+}
@@ -0,0 +1,127 @@
+use ethers_core::types::{
+    transaction::eip2930::{AccessList, AccessListItem},
+    Address, Bytes, H256, U256, U64,
+};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use super::EdenPendingTx;
+
+/// Fixed-width mirror of `EdenPendingTx`.
+///
+/// The ethers types serialize as hex strings and the tx skips `None` fields,
+/// neither of which suits a non self-describing format, so values are stored
+/// as raw bytes and limbs instead.
+#[derive(Deserialize, Serialize)]
+struct CompactTx {
+    r#type: u64,
+    hash: [u8; 32],
+    from: [u8; 20],
+    nonce: [u64; 4],
+    gas_limit: [u64; 4],
+    to: Option<[u8; 20]>,
+    data: Vec<u8>,
+    v: u64,
+    r: [u64; 4],
+    s: [u64; 4],
+    value: [u64; 4],
+    chain_id: Option<[u64; 4]>,
+    access_list: Option<Vec<([u8; 20], Vec<[u8; 32]>)>>,
+    max_priority_fee_per_gas: Option<[u64; 4]>,
+    max_fee_per_gas: Option<[u64; 4]>,
+    gas_price: Option<[u64; 4]>,
+}
+
+impl From<&EdenPendingTx> for CompactTx {
+    fn from(tx: &EdenPendingTx) -> Self {
+        Self {
+            r#type: tx.r#type.as_u64(),
+            hash: tx.hash.0,
+            from: tx.from.0,
+            nonce: tx.nonce.0,
+            gas_limit: tx.gas_limit.0,
+            to: tx.to.map(|to| to.0),
+            data: tx.data.to_vec(),
+            v: tx.v.as_u64(),
+            r: tx.r.0,
+            s: tx.s.0,
+            value: tx.value.0,
+            chain_id: tx.chain_id.map(|id| id.0),
+            access_list: tx.access_list.as_ref().map(|list| {
+                list.0
+                    .iter()
+                    .map(|item| {
+                        let keys = item.storage_keys.iter().map(|k| k.0).collect();
+                        (item.address.0, keys)
+                    })
+                    .collect()
+            }),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(|fee| fee.0),
+            max_fee_per_gas: tx.max_fee_per_gas.map(|fee| fee.0),
+            gas_price: tx.gas_price.map(|price| price.0),
+        }
+    }
+}
+
+impl From<CompactTx> for EdenPendingTx {
+    fn from(tx: CompactTx) -> Self {
+        Self {
+            r#type: U64::from(tx.r#type),
+            hash: H256(tx.hash),
+            from: Address::from(tx.from),
+            nonce: U256(tx.nonce),
+            gas_limit: U256(tx.gas_limit),
+            to: tx.to.map(Address::from),
+            data: Bytes::from(tx.data),
+            v: U64::from(tx.v),
+            r: U256(tx.r),
+            s: U256(tx.s),
+            value: U256(tx.value),
+            chain_id: tx.chain_id.map(U256),
+            access_list: tx.access_list.map(|list| {
+                AccessList(
+                    list.into_iter()
+                        .map(|(address, keys)| AccessListItem {
+                            address: Address::from(address),
+                            storage_keys: keys.into_iter().map(H256).collect(),
+                        })
+                        .collect(),
+                )
+            }),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(U256),
+            max_fee_per_gas: tx.max_fee_per_gas.map(U256),
+            gas_price: tx.gas_price.map(U256),
+        }
+    }
+}
+
+impl EdenPendingTx {
+    /// Encode into a compact `bincode` representation, far smaller than JSON
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(&CompactTx::from(self))?)
+    }
+
+    /// Decode a tx encoded by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize::<CompactTx>(bytes)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{sample_tx, TX};
+
+    #[test]
+    fn bytes_roundtrip() -> Result<()> {
+        let tx = sample_tx();
+
+        let bytes = tx.to_bytes()?;
+        let decoded = EdenPendingTx::from_bytes(&bytes)?;
+
+        assert!(bytes.len() < TX.len());
+        assert_eq!(serde_json::to_value(&decoded)?, serde_json::to_value(&tx)?);
+
+        Ok(())
+    }
+}
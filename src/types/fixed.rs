@@ -0,0 +1,77 @@
+use ethers_core::types::U256;
+
+use super::EdenPendingTx;
+
+/// Fixed-size fields of a tx for columnar storage, see
+/// [`EdenPendingTx::to_fixed_fields`]
+///
+/// Numbers and hashes are 32-byte big-endian, addresses 20 bytes. Calldata
+/// and the access list are variable-length and read from the tx directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedTxFields {
+    pub r#type: u64,
+    pub hash: [u8; 32],
+    pub from: [u8; 20],
+    pub nonce: [u8; 32],
+    pub gas_limit: [u8; 32],
+    pub to: Option<[u8; 20]>,
+    pub v: u64,
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub value: [u8; 32],
+    pub chain_id: Option<[u8; 32]>,
+    pub max_priority_fee_per_gas: Option<[u8; 32]>,
+    pub max_fee_per_gas: Option<[u8; 32]>,
+    pub gas_price: Option<[u8; 32]>,
+}
+
+// big-endian 32-byte word of `value`
+fn word(value: U256) -> [u8; 32] {
+    let mut word = [0; 32];
+    value.to_big_endian(&mut word);
+    word
+}
+
+impl EdenPendingTx {
+    /// Fixed-size view of the tx, avoiding hex or JSON conversions when
+    /// indexing in bulk
+    pub fn to_fixed_fields(&self) -> FixedTxFields {
+        FixedTxFields {
+            r#type: self.r#type.as_u64(),
+            hash: self.hash.0,
+            from: self.from.0,
+            nonce: word(self.nonce),
+            gas_limit: word(self.gas_limit),
+            to: self.to.map(|to| to.0),
+            v: self.v.as_u64(),
+            r: word(self.r),
+            s: word(self.s),
+            value: word(self.value),
+            chain_id: self.chain_id.map(word),
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas.map(word),
+            max_fee_per_gas: self.max_fee_per_gas.map(word),
+            gas_price: self.gas_price.map(word),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    #[test]
+    fn fixed_fields_are_big_endian() {
+        let tx = sample_tx();
+        let fixed = tx.to_fixed_fields();
+
+        assert_eq!(fixed.r#type, 2);
+        assert_eq!(fixed.hash, tx.hash.0);
+        assert_eq!(fixed.from, tx.from.0);
+        assert_eq!(fixed.to, tx.to.map(|to| to.0));
+        assert_eq!(U256::from_big_endian(&fixed.value), tx.value);
+        assert_eq!(U256::from_big_endian(&fixed.r), tx.r);
+        assert_eq!(fixed.chain_id.map(|id| id[31]), Some(1));
+        assert_eq!(fixed.gas_price, None);
+    }
+}
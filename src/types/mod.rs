@@ -0,0 +1,884 @@
+use std::{cmp::Ordering, sync::Arc, time::SystemTime};
+
+use ethers_core::{
+    types::{
+        transaction::eip2930::AccessList, Address, Bytes, SignatureError, Transaction as EthersTx,
+        H256, U256, U64,
+    },
+    utils::keccak256,
+};
+use eyre::eyre;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "alloy")]
+mod alloy;
+#[cfg(feature = "bincode")]
+mod binary;
+mod fixed;
+mod typed;
+
+#[cfg(feature = "alloy")]
+pub use alloy::EdenPendingTxAlloy;
+pub use fixed::FixedTxFields;
+pub use typed::EdenTxTyped;
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+/// Eden-specific pending transaction type
+///
+/// Unknown fields are ignored by default. Enable the `strict` feature to
+/// reject them instead.
+pub struct EdenPendingTx {
+    #[serde(default = "ethers_core::types::U64::zero")]
+    pub r#type: U64,
+    pub hash: H256,
+    #[serde(default = "ethers_core::types::Address::zero")]
+    pub from: Address,
+    pub nonce: U256,
+    pub gas_limit: U256,
+    pub to: Option<Address>,
+    /// Calldata, also accepted under the standard JSON-RPC name `input`
+    #[serde(alias = "input")]
+    pub data: Bytes,
+    pub v: U64,
+    pub r: U256,
+    pub s: U256,
+    pub value: U256,
+    #[serde(default, deserialize_with = "deserialize_chain_id")]
+    pub chain_id: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_list: Option<AccessList>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+}
+
+/// Fee fields valid for a tx's type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeeFields {
+    /// Legacy and EIP-2930 txs pay a flat gas price
+    Legacy { gas_price: U256 },
+    /// EIP-1559 and later txs pay under fee caps
+    Eip1559 { max_fee: U256, max_priority: U256 },
+}
+
+/// Orders txs the way block builders prioritize them: higher effective gas
+/// price first, then lower nonce.
+///
+/// `Ordering::Greater` means `a` should be included before `b`, so the result
+/// can back an `Ord` impl for a max-`BinaryHeap`.
+pub fn priority_cmp(a: &EdenPendingTx, b: &EdenPendingTx, base_fee: U256) -> Ordering {
+    a.effective_gas_price(base_fee)
+        .cmp(&b.effective_gas_price(base_fee))
+        .then_with(|| b.nonce.cmp(&a.nonce))
+}
+
+/// Min fee increase in percent a node requires to replace a pending tx
+pub const DEFAULT_PRICE_BUMP: u64 = 10;
+
+/// Gas schedule of intrinsic gas since Shanghai, see
+/// [`EdenPendingTx::intrinsic_gas`]
+const TX_GAS: u64 = 21_000;
+const TX_CREATE_GAS: u64 = 53_000;
+const TX_DATA_ZERO_GAS: u64 = 4;
+const TX_DATA_NON_ZERO_GAS: u64 = 16;
+const TX_ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+const TX_ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+const INIT_CODE_WORD_GAS: u64 = 2;
+
+/// Order of the secp256k1 curve
+const SECP256K1_N: U256 = U256([
+    0xbfd2_5e8c_d036_4141,
+    0xbaae_dce6_af48_a03b,
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+]);
+
+impl FeeFields {
+    /// `(fee cap, tip cap)`, a legacy gas price acts as both
+    fn caps(self) -> (U256, U256) {
+        match self {
+            FeeFields::Legacy { gas_price } => (gas_price, gas_price),
+            FeeFields::Eip1559 {
+                max_fee,
+                max_priority,
+            } => (max_fee, max_priority),
+        }
+    }
+}
+
+impl EdenPendingTx {
+    /// `true` for legacy (type `0x0`) txs, including payloads without a `type`
+    pub fn is_legacy(&self) -> bool {
+        self.r#type.is_zero()
+    }
+
+    /// `true` for EIP-2930 access list (type `0x1`) txs
+    pub fn is_eip2930(&self) -> bool {
+        self.r#type == U64::from(1)
+    }
+
+    /// `true` for EIP-1559 dynamic fee (type `0x2`) txs
+    pub fn is_eip1559(&self) -> bool {
+        self.r#type == U64::from(2)
+    }
+
+    /// `true` for EIP-4844 blob (type `0x3`) txs
+    pub fn is_eip4844(&self) -> bool {
+        self.r#type == U64::from(3)
+    }
+
+    /// 4-byte function selector leading the calldata, borrowed from `data`,
+    /// `None` if the calldata is shorter
+    pub fn selector(&self) -> Option<&[u8; 4]> {
+        self.data.first_chunk::<4>()
+    }
+
+    /// fee fields valid for the tx type, `None` if the payload lacks them
+    pub fn total_fee_fields(&self) -> Option<FeeFields> {
+        if self.is_legacy() || self.is_eip2930() {
+            self.gas_price
+                .map(|gas_price| FeeFields::Legacy { gas_price })
+        } else {
+            Some(FeeFields::Eip1559 {
+                max_fee: self.max_fee_per_gas?,
+                max_priority: self.max_priority_fee_per_gas?,
+            })
+        }
+    }
+
+    /// Explicit tip cap of dynamic fee txs, `None` for legacy and EIP-2930
+    /// txs, which only carry a gas price, or if the payload lacks it
+    pub fn priority_fee(&self) -> Option<U256> {
+        match self.total_fee_fields()? {
+            FeeFields::Eip1559 { max_priority, .. } => Some(max_priority),
+            FeeFields::Legacy { .. } => None,
+        }
+    }
+
+    /// Tip per gas the builder receives given the block `base_fee`, i.e. the
+    /// effective gas price above the base fee, which legacy txs pay implicitly
+    pub fn effective_tip(&self, base_fee: U256) -> U256 {
+        self.effective_gas_price(base_fee).saturating_sub(base_fee)
+    }
+
+    /// `true` if the tx pays any tip given the block `base_fee`
+    pub fn pays_tip(&self, base_fee: U256) -> bool {
+        !self.effective_tip(base_fee).is_zero()
+    }
+
+    /// `true` if `r`, `s` and `v` are well-formed.
+    ///
+    /// `r` and `s` have to be in `(0, n)`, with `s` in the lower half as
+    /// required since EIP-2. Typed txs have to carry a y-parity (`0`, `1`)
+    /// as `v`, legacy txs a pre-EIP-155 value (`27`, `28`) or an EIP-155
+    /// value matching `chain_id` when set. Doesn't recover the signer.
+    pub fn has_valid_signature(&self) -> bool {
+        let in_range = |x: U256| !x.is_zero() && x < SECP256K1_N;
+        if !in_range(self.r) || !in_range(self.s) || self.s > SECP256K1_N / 2 {
+            return false;
+        }
+
+        match (self.is_legacy(), self.v.as_u64()) {
+            (false, v) => v <= 1,
+            (true, 27 | 28) => true,
+            (true, v) if v >= 35 => match self.chain_id {
+                Some(chain_id) => chain_id == U256::from((v - 35) / 2),
+                None => true,
+            },
+            (true, _) => false,
+        }
+    }
+
+    /// Gas charged before execution under the Shanghai schedule: the base
+    /// cost, calldata bytes, access list entries and, for contract creations,
+    /// the EIP-3860 init code words.
+    ///
+    /// A tx with a lower `gas_limit` can't be included.
+    pub fn intrinsic_gas(&self) -> u64 {
+        let zeros = self.data.iter().filter(|b| **b == 0).count() as u64;
+        let non_zeros = self.data.len() as u64 - zeros;
+        let mut gas = zeros * TX_DATA_ZERO_GAS + non_zeros * TX_DATA_NON_ZERO_GAS;
+
+        if self.to.is_some() {
+            gas += TX_GAS;
+        } else {
+            let words = (self.data.len() as u64).div_ceil(32);
+            gas += TX_CREATE_GAS + words * INIT_CODE_WORD_GAS;
+        }
+
+        for item in self.access_list.iter().flat_map(|list| &list.0) {
+            gas += TX_ACCESS_LIST_ADDRESS_GAS
+                + item.storage_keys.len() as u64 * TX_ACCESS_LIST_STORAGE_KEY_GAS;
+        }
+
+        gas
+    }
+
+    /// Most wei the sender can spend on this tx, `value + gas_limit * fee cap`,
+    /// where the fee cap is the gas price of legacy txs.
+    ///
+    /// The fee cap counts as zero if the payload lacks the fee fields for its
+    /// type. Blob gas isn't included, Eden's payload carries no blob fee cap.
+    pub fn max_cost(&self) -> U256 {
+        let fee_cap = self
+            .total_fee_fields()
+            .map_or(U256::zero(), |fees| fees.caps().0);
+        self.value
+            .saturating_add(self.gas_limit.saturating_mul(fee_cap))
+    }
+
+    /// gas price actually paid per gas unit given the block `base_fee`,
+    /// zero if the payload lacks the fee fields for its type
+    pub fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match self.total_fee_fields() {
+            Some(FeeFields::Legacy { gas_price }) => gas_price,
+            Some(FeeFields::Eip1559 {
+                max_fee,
+                max_priority,
+            }) => max_fee.min(base_fee.saturating_add(max_priority)),
+            None => U256::zero(),
+        }
+    }
+
+    /// recover the signer address from the tx signature
+    pub fn recover_signer(&self) -> Result<Address, SignatureError> {
+        self.clone().into_ethers_tx().recover_from()
+    }
+
+    /// signed tx encoding as broadcast on the network, EIP-2718 typed for non-legacy txs
+    pub fn rlp(&self) -> eyre::Result<Bytes> {
+        if !(self.is_legacy() || self.is_eip2930() || self.is_eip1559()) {
+            return Err(eyre!("cannot encode tx type {}", self.r#type));
+        }
+
+        Ok(self.clone().into_ethers_tx().rlp())
+    }
+
+    /// recompute the tx hash from the signed encoding and compare it to `hash`
+    pub fn verify_hash(&self) -> eyre::Result<bool> {
+        let hash = H256::from(keccak256(self.rlp()?));
+        Ok(hash == self.hash)
+    }
+
+    /// compare inclusion priority against `other`, see [`priority_cmp`]
+    pub fn priority_cmp(&self, other: &Self, base_fee: U256) -> Ordering {
+        priority_cmp(self, other, base_fee)
+    }
+
+    /// params of an `eth_sendRawTransaction` request rebroadcasting the tx,
+    /// i.e. `["0x<rlp>"]`; errors if the encoding doesn't hash to `hash`
+    pub fn send_raw_params(&self) -> eyre::Result<serde_json::Value> {
+        let rlp = self.rlp()?;
+        let hash = H256::from(keccak256(&rlp));
+        if hash != self.hash {
+            return Err(eyre!(
+                "encoded tx hashes to {hash:?}, expected {:?}",
+                self.hash
+            ));
+        }
+
+        Ok(serde_json::json!([rlp]))
+    }
+
+    /// `true` if a node would replace `other` with `self` (replace-by-fee).
+    ///
+    /// Both txs must share `from` and `nonce`, and both the fee cap and the
+    /// tip cap of `self` must be at least `bump_percent` above those of
+    /// `other`, see [`DEFAULT_PRICE_BUMP`]. A legacy gas price counts as both
+    /// caps. Txs missing their fee fields never replace nor get replaced.
+    pub fn replaces(&self, other: &Self, bump_percent: u64) -> bool {
+        if self.from != other.from || self.nonce != other.nonce {
+            return false;
+        }
+        let (Some(new), Some(old)) = (self.total_fee_fields(), other.total_fee_fields()) else {
+            return false;
+        };
+        let ((new_fee, new_tip), (old_fee, old_tip)) = (new.caps(), old.caps());
+
+        // same integer threshold as geth's txpool
+        let bumped = |old: U256| old.saturating_mul(U256::from(100 + bump_percent)) / 100;
+        new_fee >= bumped(old_fee) && new_tip >= bumped(old_tip)
+    }
+
+    /// JSON shape of `eth_getTransactionByHash` for this tx, with the block
+    /// fields `null` as for any pending tx
+    pub fn to_eth_rpc_json(&self) -> serde_json::Value {
+        serde_json::to_value(self.clone().into_ethers_tx())
+            .expect("ethers txs always serialize to JSON")
+    }
+
+    /// Eden's JSON shape for this tx without the `v`, `r` and `s` signature
+    /// fields, e.g. for logs or shared datasets that shouldn't allow
+    /// rebroadcasting the tx
+    pub fn to_sanitized_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).expect("txs always serialize to JSON");
+        if let Some(fields) = json.as_object_mut() {
+            for field in ["v", "r", "s"] {
+                fields.remove(field);
+            }
+        }
+        json
+    }
+
+    /// Converts into an ethers tx, keeping only the fee fields valid for the
+    /// tx type: a type-2 or later tx drops a stray `gas_price`, a legacy or
+    /// EIP-2930 tx drops stray fee caps.
+    pub fn into_ethers_tx(self) -> EthersTx {
+        let legacy_fees = self.is_legacy() || self.is_eip2930();
+        let (gas_price, max_fee_per_gas, max_priority_fee_per_gas) = if legacy_fees {
+            (self.gas_price, None, None)
+        } else {
+            (None, self.max_fee_per_gas, self.max_priority_fee_per_gas)
+        };
+
+        EthersTx {
+            hash: self.hash,
+            nonce: self.nonce,
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            from: self.from,
+            to: self.to,
+            value: self.value,
+            gas_price,
+            gas: self.gas_limit,
+            input: self.data,
+            v: self.v,
+            r: self.r,
+            s: self.s,
+            transaction_type: Some(self.r#type),
+            access_list: self.access_list,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            chain_id: self.chain_id,
+            ..Default::default()
+        }
+    }
+}
+
+/// Item tagged with its client-assigned arrival sequence number and time
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Sequenced<T> {
+    /// Monotonic number assigned by the read loop, starting at `0`.
+    ///
+    /// Keeps incrementing across reconnections, so a jump between two items
+    /// marks txs lost in the handoff.
+    pub seq: u64,
+    /// Wall-clock time the read loop received the item
+    pub received_at: SystemTime,
+    /// `true` for items from the initial pending pool snapshot, `false` for live deltas
+    #[serde(default)]
+    pub snapshot: bool,
+    /// Label of the endpoint the item arrived from, see `ClientBuilder::label`
+    #[serde(default)]
+    pub label: Option<Arc<str>>,
+    /// Signed encoding of the tx, only set with `SubscriptionOptions::raw_encoding`
+    #[serde(default)]
+    pub raw: Option<Bytes>,
+    /// The delivered item
+    pub item: T,
+}
+
+/// Tx delivered together with its signed encoding, see `Subscription::raw`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RawTx {
+    /// The delivered tx
+    pub tx: EdenPendingTx,
+    /// Encoding as returned by [`EdenPendingTx::rlp`], `None` if the option
+    /// is off or the tx type can't be encoded
+    pub raw: Option<Bytes>,
+}
+
+impl From<Sequenced<EdenPendingTx>> for RawTx {
+    fn from(val: Sequenced<EdenPendingTx>) -> Self {
+        RawTx {
+            tx: val.item,
+            raw: val.raw,
+        }
+    }
+}
+
+impl From<Sequenced<EdenPendingTx>> for EdenPendingTx {
+    fn from(val: Sequenced<EdenPendingTx>) -> Self {
+        val.item
+    }
+}
+
+impl From<EdenPendingTx> for EthersTx {
+    fn from(val: EdenPendingTx) -> Self {
+        val.into_ethers_tx()
+    }
+}
+
+/// Accepts a chain id sent as a hex quantity or a plain integer
+fn deserialize_chain_id<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ChainId {
+        Number(u64),
+        Quantity(U256),
+    }
+
+    Ok(
+        Option::<ChainId>::deserialize(deserializer)?.map(|id| match id {
+            ChainId::Number(id) => U256::from(id),
+            ChainId::Quantity(id) => id,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use static_assertions::assert_impl_all;
+
+    use super::*;
+    use crate::test_utils::{sample_tx, TX};
+
+    assert_impl_all!(EdenPendingTx: Send, Sync);
+
+    fn eip1559_tx(max_fee: u64, max_priority: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.max_fee_per_gas = Some(max_fee.into());
+        tx.max_priority_fee_per_gas = Some(max_priority.into());
+        tx
+    }
+
+    fn legacy_tx(gas_price: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.r#type = U64::zero();
+        tx.gas_price = Some(gas_price.into());
+        tx.max_fee_per_gas = None;
+        tx.max_priority_fee_per_gas = None;
+        tx
+    }
+
+    #[cfg(not(feature = "strict"))]
+    #[test]
+    fn lenient_ignores_unknown_fields() {
+        let payload = TX.replacen('{', r#"{"someNewField":"0x1","#, 1);
+        let tx = serde_json::from_str::<EdenPendingTx>(&payload).unwrap();
+        assert_eq!(tx.nonce, U256::from(2));
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn strict_rejects_unknown_fields() {
+        let payload = TX.replacen('{', r#"{"someNewField":"0x1","#, 1);
+        let err = serde_json::from_str::<EdenPendingTx>(&payload).unwrap_err();
+        assert!(err.to_string().contains("someNewField"));
+    }
+
+    #[test]
+    fn priority_legacy_vs_eip1559() {
+        let base_fee = U256::from(100);
+
+        // 1559 pays min(200, 100 + 30) = 130
+        let eip1559 = eip1559_tx(200, 30);
+        let cheaper_legacy = legacy_tx(120);
+        let pricier_legacy = legacy_tx(150);
+
+        assert_eq!(eip1559.effective_gas_price(base_fee), U256::from(130));
+        assert_eq!(
+            eip1559.priority_cmp(&cheaper_legacy, base_fee),
+            Ordering::Greater
+        );
+        assert_eq!(
+            eip1559.priority_cmp(&pricier_legacy, base_fee),
+            Ordering::Less
+        );
+
+        // fee cap binds when the base fee is high
+        let high_base_fee = U256::from(190);
+        assert_eq!(eip1559.effective_gas_price(high_base_fee), U256::from(200));
+        assert_eq!(
+            eip1559.priority_cmp(&pricier_legacy, high_base_fee),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn priority_ties_break_on_lower_nonce() {
+        let base_fee = U256::from(100);
+        let mut first = legacy_tx(130);
+        let mut second = eip1559_tx(200, 30);
+        first.nonce = U256::from(1);
+        second.nonce = U256::from(2);
+
+        assert_eq!(first.priority_cmp(&second, base_fee), Ordering::Greater);
+        assert_eq!(priority_cmp(&second, &first, base_fee), Ordering::Less);
+    }
+
+    #[test]
+    fn recover_signer_errors_on_invalid_signature() {
+        let mut tx = sample_tx();
+        tx.r = U256::zero();
+        tx.s = U256::zero();
+
+        assert!(tx.recover_signer().is_err());
+    }
+
+    #[test]
+    fn missing_type_is_legacy() {
+        let payload = TX.replacen(r#""type":"0x2","#, "", 1);
+        let tx: EdenPendingTx = serde_json::from_str(&payload).unwrap();
+
+        assert!(tx.is_legacy());
+        assert!(!tx.is_eip1559());
+        assert!(sample_tx().is_eip1559());
+    }
+
+    #[test]
+    fn fee_fields_follow_tx_type() {
+        assert_eq!(
+            legacy_tx(120).total_fee_fields(),
+            Some(FeeFields::Legacy {
+                gas_price: U256::from(120)
+            })
+        );
+        assert_eq!(
+            eip1559_tx(200, 30).total_fee_fields(),
+            Some(FeeFields::Eip1559 {
+                max_fee: U256::from(200),
+                max_priority: U256::from(30)
+            })
+        );
+
+        // a legacy tx carrying only fee caps has no valid fee fields
+        let mut tx = eip1559_tx(200, 30);
+        tx.r#type = U64::zero();
+        assert_eq!(tx.total_fee_fields(), None);
+    }
+
+    #[test]
+    fn verify_hash_of_known_tx() -> eyre::Result<()> {
+        let mut tx = sample_tx();
+        assert!(tx.verify_hash()?);
+
+        // any tampered field changes the recomputed hash
+        tx.value += U256::one();
+        assert!(!tx.verify_hash()?);
+
+        tx.r#type = U64::from(3);
+        assert!(tx.verify_hash().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn access_list_survives_ethers_conversion() {
+        let mut payload: serde_json::Value = serde_json::from_str(TX).unwrap();
+        payload["type"] = "0x1".into();
+        payload["gasPrice"] = "0xc570bd200".into();
+        payload["accessList"] = serde_json::json!([
+            {
+                "address": "0x19c10fff96b80208f454034c046ccc4445cd20ba",
+                "storageKeys": [
+                    "0x0000000000000000000000000000000000000000000000000000000000000001",
+                    "0x0000000000000000000000000000000000000000000000000000000000000002"
+                ]
+            },
+            { "address": "0x19450678803d6a7bb6897ca1e793a071a100cba7", "storageKeys": [] }
+        ]);
+
+        let tx: EdenPendingTx = serde_json::from_value(payload.clone()).unwrap();
+        assert!(tx.is_eip2930());
+
+        let ethers_tx = tx.into_ethers_tx();
+        let list = ethers_tx.access_list.as_ref().unwrap();
+        assert_eq!(list.0.len(), 2);
+        assert_eq!(
+            list.0[0].storage_keys,
+            [H256::from_low_u64_be(1), H256::from_low_u64_be(2)]
+        );
+        assert!(list.0[1].storage_keys.is_empty());
+
+        // re-serializes into the same camelCase form it was read from
+        let reserialized = serde_json::to_value(&ethers_tx).unwrap();
+        assert_eq!(reserialized["accessList"], payload["accessList"]);
+    }
+
+    #[test]
+    fn replacement_needs_price_bump() {
+        let old = eip1559_tx(100, 10);
+
+        assert!(eip1559_tx(110, 11).replaces(&old, DEFAULT_PRICE_BUMP));
+        assert!(!eip1559_tx(109, 11).replaces(&old, DEFAULT_PRICE_BUMP));
+        assert!(!eip1559_tx(110, 10).replaces(&old, DEFAULT_PRICE_BUMP));
+        assert!(eip1559_tx(109, 10).replaces(&old, 0));
+
+        assert!(legacy_tx(110).replaces(&legacy_tx(100), DEFAULT_PRICE_BUMP));
+        assert!(!legacy_tx(109).replaces(&legacy_tx(100), DEFAULT_PRICE_BUMP));
+
+        let mut other_nonce = eip1559_tx(200, 20);
+        other_nonce.nonce += U256::one();
+        assert!(!other_nonce.replaces(&old, DEFAULT_PRICE_BUMP));
+    }
+
+    #[test]
+    fn send_raw_params_of_known_tx() {
+        let tx = sample_tx();
+        let params = tx.send_raw_params().unwrap();
+
+        let raw = params[0].as_str().unwrap();
+        assert!(raw.starts_with("0x02"));
+        let bytes: Bytes = raw.parse().unwrap();
+        assert_eq!(H256::from(keccak256(&bytes)), tx.hash);
+
+        let mut tampered = tx;
+        tampered.nonce += U256::one();
+        assert!(tampered.send_raw_params().is_err());
+    }
+
+    #[test]
+    fn explicit_null_fee_fields_are_none() {
+        let mut payload: serde_json::Value = serde_json::from_str(TX).unwrap();
+        for field in [
+            "maxFeePerGas",
+            "maxPriorityFeePerGas",
+            "gasPrice",
+            "accessList",
+            "chainId",
+        ] {
+            payload[field] = serde_json::Value::Null;
+        }
+
+        let tx: EdenPendingTx = serde_json::from_str(&payload.to_string()).unwrap();
+        assert_eq!(tx.max_fee_per_gas, None);
+        assert_eq!(tx.max_priority_fee_per_gas, None);
+        assert_eq!(tx.gas_price, None);
+        assert_eq!(tx.access_list, None);
+        assert_eq!(tx.chain_id, None);
+    }
+
+    #[test]
+    fn sanitized_json_omits_signature() {
+        let tx = sample_tx();
+        let json = tx.to_sanitized_json();
+
+        for field in ["v", "r", "s"] {
+            assert!(json.get(field).is_none(), "{field}");
+        }
+        assert_eq!(json["hash"], serde_json::to_value(tx.hash).unwrap());
+        assert_eq!(json["from"], serde_json::to_value(tx.from).unwrap());
+        assert_eq!(json["to"], serde_json::to_value(tx.to).unwrap());
+        assert_eq!(json["value"], serde_json::to_value(tx.value).unwrap());
+        assert_eq!(
+            json["gasLimit"],
+            serde_json::to_value(tx.gas_limit).unwrap()
+        );
+    }
+
+    #[test]
+    fn eth_rpc_json_shape() {
+        let json = sample_tx().to_eth_rpc_json();
+        let eden: serde_json::Value = serde_json::from_str(TX).unwrap();
+
+        for field in ["blockHash", "blockNumber", "transactionIndex"] {
+            assert_eq!(json[field], serde_json::Value::Null, "{field}");
+        }
+        assert_eq!(json["gas"], eden["gasLimit"]);
+        assert_eq!(json["input"], eden["data"]);
+        for field in [
+            "hash",
+            "from",
+            "to",
+            "nonce",
+            "value",
+            "type",
+            "chainId",
+            "maxFeePerGas",
+            "maxPriorityFeePerGas",
+            "r",
+            "s",
+        ] {
+            assert_eq!(json[field], eden[field], "{field}");
+        }
+        assert!(json.get("gasLimit").is_none());
+        assert!(json.get("data").is_none());
+    }
+
+    #[test]
+    fn ethers_conversion_drops_fee_fields_of_other_types() {
+        let mut tx = sample_tx();
+        tx.gas_price = Some(U256::from(7));
+
+        for ethers_tx in [tx.clone().into_ethers_tx(), EthersTx::from(tx.clone())] {
+            assert_eq!(ethers_tx.gas_price, None);
+            assert_eq!(ethers_tx.max_fee_per_gas, tx.max_fee_per_gas);
+            assert_eq!(
+                ethers_tx.max_priority_fee_per_gas,
+                tx.max_priority_fee_per_gas
+            );
+        }
+
+        for r#type in [0, 1] {
+            tx.r#type = U64::from(r#type);
+            for ethers_tx in [tx.clone().into_ethers_tx(), EthersTx::from(tx.clone())] {
+                assert_eq!(ethers_tx.gas_price, Some(U256::from(7)));
+                assert_eq!(ethers_tx.max_fee_per_gas, None);
+                assert_eq!(ethers_tx.max_priority_fee_per_gas, None);
+            }
+        }
+    }
+
+    #[test]
+    fn tips_per_type() {
+        let base_fee = U256::from(30_000_000_000u64);
+
+        // 0x2faf080 tip under a 0xc570bd200 fee cap
+        let mut tx = sample_tx();
+        assert_eq!(tx.priority_fee(), Some(U256::from(0x2faf080)));
+        assert_eq!(tx.effective_tip(base_fee), U256::from(0x2faf080));
+        assert!(tx.pays_tip(base_fee));
+
+        tx.max_priority_fee_per_gas = Some(U256::zero());
+        assert!(!tx.pays_tip(base_fee));
+
+        // legacy txs tip whatever their gas price exceeds the base fee by
+        tx.r#type = U64::zero();
+        tx.gas_price = Some(base_fee + 5);
+        assert_eq!(tx.priority_fee(), None);
+        assert_eq!(tx.effective_tip(base_fee), U256::from(5));
+        assert!(tx.pays_tip(base_fee));
+        assert!(!tx.pays_tip(base_fee + 5));
+    }
+
+    #[test]
+    fn signature_validation() {
+        // the sample's EIP-155 `v` of 38 doesn't fit its type, its y-parity does
+        let mut tx = sample_tx();
+        assert!(!tx.has_valid_signature());
+        tx.v = U64::one();
+        assert!(tx.has_valid_signature());
+
+        let mut bad = tx.clone();
+        bad.r = U256::zero();
+        assert!(!bad.has_valid_signature());
+
+        let mut bad = tx.clone();
+        bad.s = SECP256K1_N;
+        assert!(!bad.has_valid_signature());
+
+        // the malleable twin of a valid signature
+        let mut bad = tx.clone();
+        bad.s = SECP256K1_N - tx.s;
+        assert!(!bad.has_valid_signature());
+
+        let mut bad = tx.clone();
+        bad.v = U64::from(29);
+        assert!(!bad.has_valid_signature());
+
+        // typed txs only carry a y-parity
+        for v in [27, 37] {
+            let mut bad = tx.clone();
+            bad.v = U64::from(v);
+            assert!(!bad.has_valid_signature());
+        }
+
+        let mut legacy = tx;
+        legacy.r#type = U64::zero();
+        for v in [27, 28, 37, 38] {
+            legacy.v = U64::from(v);
+            assert!(legacy.has_valid_signature());
+        }
+
+        // y-parity without the legacy offset
+        for v in [0, 1] {
+            legacy.v = U64::from(v);
+            assert!(!legacy.has_valid_signature());
+        }
+
+        // EIP-155 v of chain 5 on a chain 1 tx
+        legacy.v = U64::from(45);
+        assert!(!legacy.has_valid_signature());
+    }
+
+    #[test]
+    fn intrinsic_gas_per_schedule() {
+        use ethers_core::types::transaction::eip2930::AccessListItem;
+
+        // 292 calldata bytes, 210 of them zero
+        let mut tx = sample_tx();
+        assert_eq!(tx.intrinsic_gas(), 23_152);
+
+        tx.data = Bytes::new();
+        assert_eq!(tx.intrinsic_gas(), 21_000);
+
+        tx.data = vec![0, 1].into();
+        assert_eq!(tx.intrinsic_gas(), 21_020);
+
+        tx.access_list = Some(AccessList(vec![AccessListItem {
+            address: Address::zero(),
+            storage_keys: vec![H256::zero(); 2],
+        }]));
+        assert_eq!(tx.intrinsic_gas(), 21_020 + 2_400 + 2 * 1_900);
+
+        // creation with 33 non-zero bytes of init code, i.e. two words
+        tx.access_list = None;
+        tx.to = None;
+        tx.data = vec![1; 33].into();
+        assert_eq!(tx.intrinsic_gas(), 53_000 + 33 * 16 + 2 * 2);
+    }
+
+    #[test]
+    fn max_cost_per_type() {
+        let mut tx = sample_tx();
+        // value + gasLimit * maxFeePerGas of the sample
+        let expected =
+            U256::from(0x83019dfc17b0000u64) + U256::from(0x7a120) * U256::from(0xc570bd200u64);
+        assert_eq!(tx.max_cost(), expected);
+
+        tx.r#type = U64::zero();
+        tx.gas_price = Some(U256::from(7));
+        assert_eq!(tx.max_cost(), tx.value + tx.gas_limit * 7);
+
+        tx.gas_price = None;
+        assert_eq!(tx.max_cost(), tx.value);
+    }
+
+    #[test]
+    fn selector_of_calldata() {
+        let mut tx = sample_tx();
+        tx.data = vec![0xa9, 0x05, 0x9c, 0xbb, 0x01].into();
+        assert_eq!(tx.selector(), Some(&[0xa9, 0x05, 0x9c, 0xbb]));
+
+        tx.data = vec![0xa9, 0x05, 0x9c].into();
+        assert_eq!(tx.selector(), None);
+    }
+
+    #[test]
+    fn input_is_an_alias_of_data() {
+        let mut payload: serde_json::Value = serde_json::from_str(TX).unwrap();
+        let data = payload.as_object_mut().unwrap().remove("data").unwrap();
+        payload["input"] = data;
+
+        let tx: EdenPendingTx = serde_json::from_value(payload).unwrap();
+        assert_eq!(tx.data, sample_tx().data);
+    }
+
+    #[test]
+    fn chain_id_as_hex_or_decimal() {
+        let mut payload: serde_json::Value = serde_json::from_str(TX).unwrap();
+
+        for chain_id in [serde_json::json!("0x1"), serde_json::json!(1)] {
+            payload["chainId"] = chain_id;
+            let tx: EdenPendingTx = serde_json::from_value(payload.clone()).unwrap();
+            assert_eq!(tx.chain_id, Some(U256::one()));
+        }
+
+        payload["chainId"] = serde_json::json!("mainnet");
+        assert!(serde_json::from_value::<EdenPendingTx>(payload.clone()).is_err());
+
+        payload.as_object_mut().unwrap().remove("chainId");
+        let tx: EdenPendingTx = serde_json::from_value(payload).unwrap();
+        assert_eq!(tx.chain_id, None);
+    }
+}
@@ -0,0 +1,202 @@
+use ethers_core::types::{transaction::eip2930::AccessList, Address, Bytes, U256};
+use eyre::{eyre, Result};
+
+use super::EdenPendingTx;
+
+/// Tx fields split by tx type, see [`EdenPendingTx::typed`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdenTxTyped {
+    /// Type `0x0`, optionally replay protected through EIP-155
+    Legacy {
+        chain_id: Option<U256>,
+        nonce: U256,
+        gas_price: U256,
+        gas_limit: U256,
+        to: Option<Address>,
+        value: U256,
+        data: Bytes,
+    },
+    /// Type `0x1`
+    Eip2930 {
+        chain_id: U256,
+        nonce: U256,
+        gas_price: U256,
+        gas_limit: U256,
+        to: Option<Address>,
+        value: U256,
+        data: Bytes,
+        access_list: AccessList,
+    },
+    /// Type `0x2`
+    Eip1559 {
+        chain_id: U256,
+        nonce: U256,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        gas_limit: U256,
+        to: Option<Address>,
+        value: U256,
+        data: Bytes,
+        access_list: AccessList,
+    },
+    /// Type `0x3`, blob fields are not part of the feed
+    Eip4844 {
+        chain_id: U256,
+        nonce: U256,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        gas_limit: U256,
+        to: Address,
+        value: U256,
+        data: Bytes,
+        access_list: AccessList,
+    },
+}
+
+impl EdenPendingTx {
+    /// View of the tx carrying exactly the fields valid for its type.
+    ///
+    /// Errors if the type is unknown or a field required by it is missing. A
+    /// missing access list on a typed tx is read as empty.
+    pub fn typed(&self) -> Result<EdenTxTyped> {
+        let missing = |field: &str| eyre!("type {} tx is missing `{field}`", self.r#type);
+        let chain_id = || self.chain_id.ok_or_else(|| missing("chainId"));
+        let gas_price = || self.gas_price.ok_or_else(|| missing("gasPrice"));
+        let max_fee_per_gas = || self.max_fee_per_gas.ok_or_else(|| missing("maxFeePerGas"));
+        let max_priority_fee_per_gas = || {
+            self.max_priority_fee_per_gas
+                .ok_or_else(|| missing("maxPriorityFeePerGas"))
+        };
+        let access_list = self.access_list.clone().unwrap_or_default();
+
+        let typed = match self.r#type.as_u64() {
+            0 => EdenTxTyped::Legacy {
+                chain_id: self.chain_id,
+                nonce: self.nonce,
+                gas_price: gas_price()?,
+                gas_limit: self.gas_limit,
+                to: self.to,
+                value: self.value,
+                data: self.data.clone(),
+            },
+            1 => EdenTxTyped::Eip2930 {
+                chain_id: chain_id()?,
+                nonce: self.nonce,
+                gas_price: gas_price()?,
+                gas_limit: self.gas_limit,
+                to: self.to,
+                value: self.value,
+                data: self.data.clone(),
+                access_list,
+            },
+            2 => EdenTxTyped::Eip1559 {
+                chain_id: chain_id()?,
+                nonce: self.nonce,
+                max_fee_per_gas: max_fee_per_gas()?,
+                max_priority_fee_per_gas: max_priority_fee_per_gas()?,
+                gas_limit: self.gas_limit,
+                to: self.to,
+                value: self.value,
+                data: self.data.clone(),
+                access_list,
+            },
+            3 => EdenTxTyped::Eip4844 {
+                chain_id: chain_id()?,
+                nonce: self.nonce,
+                max_fee_per_gas: max_fee_per_gas()?,
+                max_priority_fee_per_gas: max_priority_fee_per_gas()?,
+                gas_limit: self.gas_limit,
+                to: self.to.ok_or_else(|| missing("to"))?,
+                value: self.value,
+                data: self.data.clone(),
+                access_list,
+            },
+            ty => return Err(eyre!("unsupported tx type {ty}")),
+        };
+
+        Ok(typed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers_core::types::U64;
+
+    use super::*;
+    use crate::test_utils::sample_tx;
+
+    fn with_type(ty: u64) -> EdenPendingTx {
+        let mut tx = sample_tx();
+        tx.r#type = U64::from(ty);
+        tx.gas_price = Some(U256::from(7));
+        tx
+    }
+
+    #[test]
+    fn legacy() {
+        let mut tx = with_type(0);
+        tx.chain_id = None;
+
+        let EdenTxTyped::Legacy {
+            chain_id,
+            gas_price,
+            ..
+        } = tx.typed().unwrap()
+        else {
+            panic!("expected legacy");
+        };
+        assert_eq!(chain_id, None);
+        assert_eq!(gas_price, U256::from(7));
+    }
+
+    #[test]
+    fn eip2930() {
+        let mut tx = with_type(1);
+        tx.access_list = None;
+
+        let EdenTxTyped::Eip2930 {
+            chain_id,
+            access_list,
+            ..
+        } = tx.typed().unwrap()
+        else {
+            panic!("expected eip-2930");
+        };
+        assert_eq!(chain_id, U256::one());
+        assert!(access_list.0.is_empty());
+
+        tx.chain_id = None;
+        assert!(tx.typed().is_err());
+    }
+
+    #[test]
+    fn eip1559() {
+        let tx = sample_tx();
+
+        let EdenTxTyped::Eip1559 {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            ..
+        } = tx.typed().unwrap()
+        else {
+            panic!("expected eip-1559");
+        };
+        assert_eq!(max_fee_per_gas, U256::from(53_000_000_000u64));
+        assert_eq!(max_priority_fee_per_gas, U256::from(50_000_000));
+    }
+
+    #[test]
+    fn eip4844() {
+        let mut tx = with_type(3);
+        assert!(matches!(tx.typed(), Ok(EdenTxTyped::Eip4844 { .. })));
+
+        // blob txs cannot create contracts
+        tx.to = None;
+        assert!(tx.typed().is_err());
+    }
+
+    #[test]
+    fn unknown_type() {
+        assert!(with_type(0x7e).typed().is_err());
+    }
+}